@@ -1,35 +1,36 @@
 extern crate algorithms;
 extern crate docopt;
 extern crate rand;
-extern crate rustc_serialize;
 extern crate time;
 
-use docopt::Docopt;
-use rand::Rng;
+use algorithms::sorting::{self, Sorter};
+use algorithms::sorting::heap::{self, pq::MinPQ};
+use algorithms::sorting::merge::top_down;
+use algorithms::sorting::quick;
+use docopt::{ArgvMap, Docopt};
+use rand::{Rand, Rng};
 use rand::ThreadRng;
 
 static USAGE: &'static str = "
 Usage: sort_compare [-n N] [-t T] <algorithm1> <algorithm2>
+       sort_compare [-n N] [-t T] [-k K] --arity
+       sort_compare [-n N] [-t T] [-p P] --threads
+       sort_compare --list
        sort_compare --help
 
 Options:
     -h --help   Show this message
+    -l --list   List the algorithms
+    -a --arity  Compare heaps of arity 2, 3, 4 and 8
+    --threads   Compare parallel sorts on 1, 2, 4, ... threads
     -n N        Set input size [default: 1000]
     -t T        Set number of repetitions [default: 100]
+    -k K        Set key size in 64-bit words for --arity [default: 1]
+    -p P        Set the most threads for --threads [default: all]
 ";
 
-type SortFunction = fn(&mut Vec<i64>) -> ();
-
-#[derive(RustcDecodable, Debug)]
-struct Args {
-    arg_algorithm1: String,
-    arg_algorithm2: String,
-    flag_n: Option<usize>,
-    flag_t: Option<usize>,
-}
-
 fn time_sort(rng: &mut ThreadRng,
-             sort: SortFunction,
+             sorter: &dyn Sorter<i64>,
              n: usize,
              t: usize) -> i64 {
     let mut total = 0;
@@ -39,41 +40,176 @@ fn time_sort(rng: &mut ThreadRng,
             input.push(rng.gen());
         }
         let start_time = time::get_time();
-        sort(&mut input);
+        sorter.sort(&mut input);
         let end_time = time::get_time();
         total += (end_time - start_time).num_microseconds().unwrap();
-        assert!(algorithms::sorting::is_sorted(&input));
+        assert!(sorting::is_sorted(&input));
     }
     total
 }
 
-fn get_sort(name: &String) -> SortFunction {
-    match name.as_ref() {
-        "heap"      => algorithms::sorting::heap::sort::sort,
-        "quick"     => algorithms::sorting::quick::sort,
-        "merge_bu"  => algorithms::sorting::merge::bottom_up::sort,
-        "merge_td"  => algorithms::sorting::merge::top_down::sort,
-        "shell"     => algorithms::sorting::shell::sort,
-        "insertion" => algorithms::sorting::insertion::sort,
-        _           => algorithms::sorting::selection::sort,
+const ARITIES: [usize; 4] = [2, 3, 4, 8];
+
+// Time heapsort and a priority queue that inserts, then removes, every
+// element, for each heap arity.
+fn time_arities<T: Ord + Rand + Clone>(rng: &mut ThreadRng,
+                                       n: usize,
+                                       t: usize) -> Vec<(i64, i64)> {
+    let mut totals = vec![(0, 0); ARITIES.len()];
+    for _ in 0..t {
+        let input: Vec<T> = rng.gen_iter().take(n).collect();
+        for (&d, total) in ARITIES.iter().zip(totals.iter_mut()) {
+            let mut v = input.clone();
+            let start_time = time::get_time();
+            heap::sort::sort_with_arity(&mut v, d);
+            let end_time = time::get_time();
+            total.0 += (end_time - start_time).num_microseconds().unwrap();
+            assert!(sorting::is_sorted(&v));
+
+            let start_time = time::get_time();
+            let mut pq = MinPQ::with_arity(d);
+            pq.extend(input.iter().cloned());
+            while pq.pop().is_some() {}
+            let end_time = time::get_time();
+            total.1 += (end_time - start_time).num_microseconds().unwrap();
+        }
+    }
+    totals
+}
+
+fn compare_arities(rng: &mut ThreadRng, n: usize, t: usize, k: usize) {
+    let totals = match k {
+        1 => time_arities::<[u64; 1]>(rng, n, t),
+        2 => time_arities::<[u64; 2]>(rng, n, t),
+        4 => time_arities::<[u64; 4]>(rng, n, t),
+        8 => time_arities::<[u64; 8]>(rng, n, t),
+        16 => time_arities::<[u64; 16]>(rng, n, t),
+        _ => fail("-k must be 1, 2, 4, 8 or 16"),
+    };
+    println!("For {} random {}-byte keys, microseconds per run", n, 8*k);
+    println!("    {:>6} {:>10} {:>10}", "arity", "heapsort", "pq");
+    for (d, &(sort, pq)) in ARITIES.iter().zip(totals.iter()) {
+        println!("    {:>6} {:>10.1} {:>10.1}", d,
+                 sort as f64 / t as f64, pq as f64 / t as f64);
     }
 }
 
+// A parallel sort that takes the number of threads to use.
+type ParSort = fn(&mut [i64], usize);
+
+// Time a parallel sort on `threads` threads.
+fn time_par_sort(rng: &mut ThreadRng,
+                 sort: ParSort,
+                 threads: usize,
+                 n: usize,
+                 t: usize) -> i64 {
+    let mut total = 0;
+    for _ in 0..t {
+        let mut input: Vec<i64> = rng.gen_iter().take(n).collect();
+        let start_time = time::get_time();
+        sort(&mut input, threads);
+        let end_time = time::get_time();
+        total += (end_time - start_time).num_microseconds().unwrap();
+        assert!(sorting::is_sorted(&input));
+    }
+    total
+}
+
+fn compare_threads(rng: &mut ThreadRng, n: usize, t: usize,
+                   max_threads: usize) {
+    let mut counts = vec![1];
+    while counts[counts.len() - 1] * 2 <= max_threads {
+        let next = counts[counts.len() - 1] * 2;
+        counts.push(next);
+    }
+    if counts[counts.len() - 1] < max_threads { counts.push(max_threads); }
+
+    let sorts: [(&str, ParSort); 2] =
+        [("merge_td", top_down::par_sort), ("quick", quick::par_sort)];
+    println!("For {} random ints, on a machine with {} threads",
+             n, sorting::available_threads());
+    println!("    {:>10} {:>8} {:>12} {:>8}",
+             "algorithm", "threads", "ms per run", "speedup");
+    for &(name, sort) in &sorts {
+        let base = time_par_sort(rng, sort, 1, n, t);
+        for &threads in &counts {
+            let total = if threads == 1 { base }
+                        else { time_par_sort(rng, sort, threads, n, t) };
+            println!("    {:>10} {:>8} {:>12.2} {:>8.2}",
+                     name, threads, total as f64 / t as f64 / 1000.0,
+                     base as f64 / total as f64);
+        }
+    }
+}
+
+fn get_sort(name: &str) -> Box<dyn Sorter<i64>> {
+    sorting::sorter(name).unwrap_or_else(|e| fail(&e.to_string()))
+}
+
+fn list_sorts() {
+    for s in sorting::sorters::<i64>() {
+        let line = format!("{:15} {:10} {}",
+                           s.name(),
+                           if s.is_stable() { "stable" } else { "" },
+                           if s.is_in_place() { "in place" } else { "" });
+        println!("{}", line.trim_end());
+    }
+}
+
+fn parse_count(args: &ArgvMap, flag: &str) -> usize {
+    args.get_str(flag).parse().unwrap_or_else(|_| {
+        fail(&format!("{} must be a non-negative integer", flag))
+    })
+}
+
+// Report a usage error on stderr and exit with a failure status.
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
 fn main() {
-    let args: Args = Docopt::new(USAGE)
-                            .and_then(|d| d.decode())
-                            .unwrap_or_else(|e| e.exit());
+    let args: ArgvMap = Docopt::new(USAGE)
+                               .and_then(|d| d.parse())
+                               .unwrap_or_else(|e| e.exit());
+    if args.get_bool("--list") {
+        list_sorts();
+        return;
+    }
+    if args.get_bool("--threads") {
+        let mut rng = rand::thread_rng();
+        let max_threads = match args.get_str("-p") {
+            "all" => sorting::available_threads(),
+            _ => parse_count(&args, "-p"),
+        };
+        if max_threads == 0 {
+            fail("-p must be at least 1");
+        }
+        compare_threads(&mut rng, parse_count(&args, "-n"),
+                        parse_count(&args, "-t"), max_threads);
+        return;
+    }
+    if args.get_bool("--arity") {
+        let mut rng = rand::thread_rng();
+        compare_arities(&mut rng, parse_count(&args, "-n"),
+                        parse_count(&args, "-t"), parse_count(&args, "-k"));
+        return;
+    }
 
-    let n = args.flag_n.unwrap_or(1000);
-    let t = args.flag_n.unwrap_or(100);
+    let algorithm1 = args.get_str("<algorithm1>");
+    let algorithm2 = args.get_str("<algorithm2>");
+    let n = parse_count(&args, "-n");
+    let t = parse_count(&args, "-t");
 
     let mut rng = rand::thread_rng();
-    let t1 = time_sort(&mut rng, get_sort(&args.arg_algorithm1), n, t);
-    let t2 = time_sort(&mut rng, get_sort(&args.arg_algorithm2), n, t);
+    let sorter1 = get_sort(algorithm1);
+    let sorter2 = get_sort(algorithm2);
+    let t1 = time_sort(&mut rng, &*sorter1, n, t);
+    let t2 = time_sort(&mut rng, &*sorter2, n, t);
 
     println!("For {} random ints", n);
     println!("    {} sort is {:.2} times faster than {} sort",
-             args.arg_algorithm1,
+             algorithm1,
              (t2 as f64) / (t1 as f64),
-             args.arg_algorithm2);
+             algorithm2);
 }
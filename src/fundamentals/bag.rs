@@ -0,0 +1,163 @@
+//! Algorithm 1.4: Bag.
+
+use std::fmt;
+use std::iter::FromIterator;
+
+struct Node<T> {
+    value: T,
+    next: Option<Box<Node<T>>>,
+}
+
+/// A collection that supports adding elements and iterating over them, but not
+/// removing them. It's based on a linked list; iteration visits the elements
+/// in reverse order of insertion.
+pub struct Bag<T> {
+    first: Option<Box<Node<T>>>,
+    n: usize,
+}
+
+impl<T> Bag<T> {
+    /// Constructs a new, empty bag.
+    pub fn new() -> Bag<T> {
+        Bag { first: None, n: 0 }
+    }
+
+    /// Returns `true` if the bag is empty.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the number of elements in the bag.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Adds an element to the bag.
+    pub fn add(&mut self, value: T) {
+        self.first = Some(Box::new(Node { value, next: self.first.take() }));
+        self.n += 1;
+    }
+
+    /// Returns an iterator over the elements of the bag.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.first.as_deref(), n: self.n }
+    }
+}
+
+impl<T> Drop for Bag<T> {
+    // Unlink the nodes one by one to avoid a recursive drop.
+    fn drop(&mut self) {
+        let mut link = self.first.take();
+        while let Some(mut node) = link {
+            link = node.next.take();
+        }
+    }
+}
+
+/// Iterator over the elements of a `Bag`.
+pub struct Iter<'a, T: 'a> {
+    next: Option<&'a Node<T>>,
+    n: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.n -= 1;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+// `Iter` is `Clone` even if `T` isn't.
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Iter<'a, T> {
+        Iter { next: self.next, n: self.n }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Bag<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for Bag<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Bag<T> {
+        let mut b = Bag::new();
+        b.extend(iter);
+        b
+    }
+}
+
+impl<T> Extend<T> for Bag<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<T> Default for Bag<T> {
+    fn default() -> Bag<T> {
+        Bag::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Bag<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bag;
+
+    #[test]
+    fn empty_bag() {
+        let b: Bag<isize> = Bag::new();
+        assert!(b.is_empty());
+        assert_eq!(b.size(), 0);
+        assert_eq!(b.iter().next(), None);
+    }
+
+    #[test]
+    fn ten_elements() {
+        let mut b = Bag::new();
+        for i in 0..10 {
+            b.add(i);
+        }
+        assert!( ! b.is_empty());
+        assert_eq!(b.size(), 10);
+        assert_eq!(b.iter().len(), 10);
+        let mut seen: Vec<isize> = b.iter().cloned().collect();
+        seen.sort();
+        assert_eq!(seen, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iterate_twice() {
+        let b: Bag<isize> = (0..3).collect();
+        let mut total = 0;
+        for x in &b {
+            for y in &b {
+                total += x * y;
+            }
+        }
+        assert_eq!(total, 9);
+        assert_eq!(format!("{:?}", b), "[2, 1, 0]");
+    }
+}
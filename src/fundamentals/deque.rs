@@ -0,0 +1,282 @@
+//! Double-ended queue (Exercise 1.3.33).
+
+use std::fmt;
+use std::iter::FromIterator;
+
+/// A double-ended queue implementation based on a resizing circular array.
+/// Adding and removing elements at either end takes constant amortized time.
+pub struct Deque<T> {
+    a: Vec<Option<T>>,
+    // index of the first element
+    head: usize,
+    n: usize,
+}
+
+impl<T> Deque<T> {
+    /// Constructs a new, empty deque.
+    pub fn new() -> Deque<T> {
+        Deque { a: vec![], head: 0, n: 0 }
+    }
+
+    /// Returns `true` if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Adds an element to the front of the deque.
+    pub fn push_front(&mut self, value: T) {
+        self.grow_if_full();
+        let cap = self.a.len();
+        self.head = (self.head + cap - 1) % cap;
+        self.a[self.head] = Some(value);
+        self.n += 1;
+    }
+
+    /// Adds an element to the back of the deque.
+    pub fn push_back(&mut self, value: T) {
+        self.grow_if_full();
+        let i = self.index(self.n);
+        self.a[i] = Some(value);
+        self.n += 1;
+    }
+
+    /// Removes the element at the front of the deque and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() { return None; }
+        let value = self.a[self.head].take();
+        self.head = (self.head + 1) % self.a.len();
+        self.n -= 1;
+        self.shrink_if_sparse();
+        value
+    }
+
+    /// Removes the element at the back of the deque and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() { return None; }
+        let i = self.index(self.n - 1);
+        let value = self.a[i].take();
+        self.n -= 1;
+        self.shrink_if_sparse();
+        value
+    }
+
+    /// Returns a reference to the element at the front of the deque, or
+    /// `None` if it's empty.
+    pub fn peek_front(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// Returns a reference to the element at the back of the deque, or `None`
+    /// if it's empty.
+    pub fn peek_back(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+
+    /// Returns an iterator over the deque, from front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { deque: self, front: 0, back: self.n }
+    }
+
+    // Position in `a` of the element with the given offset from the front.
+    fn index(&self, offset: usize) -> usize {
+        (self.head + offset) % self.a.len()
+    }
+
+    fn grow_if_full(&mut self) {
+        if self.n == self.a.len() {
+            let cap = if self.n == 0 { 1 } else { 2 * self.n };
+            self.resize(cap);
+        }
+    }
+
+    fn shrink_if_sparse(&mut self) {
+        if self.n > 0 && self.n == self.a.len() / 4 {
+            let cap = self.a.len() / 2;
+            self.resize(cap);
+        }
+    }
+
+    // Move the elements to a new array of size `cap`, starting at index 0.
+    fn resize(&mut self, cap: usize) {
+        let mut a: Vec<Option<T>> = (0..cap).map(|_| None).collect();
+        for (k, slot) in a.iter_mut().enumerate().take(self.n) {
+            let i = self.index(k);
+            *slot = self.a[i].take();
+        }
+        self.a = a;
+        self.head = 0;
+    }
+}
+
+/// Iterator over the elements of a `Deque`.
+pub struct Iter<'a, T: 'a> {
+    deque: &'a Deque<T>,
+    // offsets of the remaining elements: front..back
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back { return None; }
+        let i = self.deque.index(self.front);
+        self.front += 1;
+        self.deque.a[i].as_ref()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        let i = self.deque.index(self.back);
+        self.deque.a[i].as_ref()
+    }
+}
+
+/// Iterator that moves elements out of a `Deque`, from front to back.
+pub struct IntoIter<T>(Deque<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.n, Some(self.0.n))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> IntoIterator for Deque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Deque<T> {
+        let mut d = Deque::new();
+        d.extend(iter);
+        d
+    }
+}
+
+/// Adds the elements to the back of the deque.
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Deque<T> {
+        Deque::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deque;
+
+    #[test]
+    fn empty_deque() {
+        let mut d: Deque<isize> = Deque::new();
+        assert!(d.is_empty());
+        assert_eq!(d.len(), 0);
+        assert_eq!(d.pop_front(), None);
+        assert_eq!(d.pop_back(), None);
+        assert_eq!(d.peek_front(), None);
+    }
+
+    #[test]
+    fn both_ends() {
+        let mut d = Deque::new();
+        d.push_back(2);
+        d.push_front(1);
+        d.push_back(3);
+        d.push_front(0);
+        assert_eq!(d.len(), 4);
+        assert_eq!(d.peek_front(), Some(&0));
+        assert_eq!(d.peek_back(), Some(&3));
+        assert_eq!(d.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(d.pop_back(), Some(3));
+        assert_eq!(d.pop_front(), Some(0));
+        assert_eq!(d.pop_front(), Some(1));
+        assert_eq!(d.pop_back(), Some(2));
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn as_stack_and_queue() {
+        let mut d = Deque::new();
+        for i in 0..100 {
+            d.push_front(i);
+        }
+        for i in 0..100 {
+            assert_eq!(d.pop_front(), Some(99 - i));
+        }
+        for i in 0..100 {
+            d.push_back(i);
+        }
+        for i in 0..100 {
+            assert_eq!(d.pop_front(), Some(i));
+        }
+        assert_eq!(d.pop_back(), None);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let mut d: Deque<isize> = (0..8).collect();
+        for i in 8..20 {
+            d.pop_front();
+            d.push_back(i);
+        }
+        assert_eq!(d.iter().cloned().collect::<Vec<_>>(),
+                   (12..20).collect::<Vec<_>>());
+        assert_eq!(d.iter().rev().cloned().collect::<Vec<_>>(),
+                   (12..20).rev().collect::<Vec<_>>());
+        assert_eq!(format!("{:?}", d), "[12, 13, 14, 15, 16, 17, 18, 19]");
+        assert_eq!(d.into_iter().rev().collect::<Vec<_>>(),
+                   (12..20).rev().collect::<Vec<_>>());
+    }
+}
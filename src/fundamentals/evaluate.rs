@@ -0,0 +1,373 @@
+//! Dijkstra's two-stack algorithm for expression evaluation (page 129), with
+//! extensions for operator precedence and postfix notation.
+//!
+//! Expressions consist of numbers, the binary operators `+`, `-`, `*` and `/`,
+//! the unary prefix operator `sqrt`, and parentheses. Tokens don't have to be
+//! separated by spaces, so `(1+(2*3))` and `( 1 + ( 2 * 3 ) )` are the same.
+
+use std::error;
+use std::fmt;
+
+use super::stack::Stack;
+
+/// An error in an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The input contains something that isn't a number, an operator or a
+    /// parenthesis.
+    InvalidToken(String),
+    /// A token appears in a position where it doesn't make sense, e.g. two
+    /// numbers in a row in an infix expression.
+    UnexpectedToken(String),
+    /// An operator doesn't have enough operands.
+    MissingOperand,
+    /// There are operands left over that no operator applies to.
+    MissingOperator,
+    /// Parentheses aren't balanced.
+    UnbalancedParentheses,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidToken(ref t)    => write!(f, "invalid token {}", t),
+            Error::UnexpectedToken(ref t) => write!(f, "unexpected {}", t),
+            Error::MissingOperand         => f.write_str("missing operand"),
+            Error::MissingOperator        => f.write_str("missing operator"),
+            Error::UnbalancedParentheses  => f.write_str("unbalanced parens"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Sqrt,
+}
+
+impl Op {
+    fn precedence(self) -> usize {
+        match self {
+            Op::Add | Op::Sub => 1,
+            Op::Mul | Op::Div => 2,
+            Op::Sqrt          => 3,
+        }
+    }
+
+    fn is_unary(self) -> bool {
+        self == Op::Sqrt
+    }
+
+    // Pop the operands for this operator from `vals` and push the result.
+    fn apply(self, vals: &mut Stack<f64>) -> Result<(), Error> {
+        let y = vals.pop().ok_or(Error::MissingOperand)?;
+        let result = if self.is_unary() {
+            y.sqrt()
+        } else {
+            let x = vals.pop().ok_or(Error::MissingOperand)?;
+            match self {
+                Op::Add  => x + y,
+                Op::Sub  => x - y,
+                Op::Mul  => x * y,
+                Op::Div  => x / y,
+                Op::Sqrt => unreachable!(),
+            }
+        };
+        vals.push(result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Op::Add  => "+",
+            Op::Sub  => "-",
+            Op::Mul  => "*",
+            Op::Div  => "/",
+            Op::Sqrt => "sqrt",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Op(Op),
+    LeftParen,
+    RightParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Num(x)     => write!(f, "{}", x),
+            Token::Op(op)     => write!(f, "{}", op),
+            Token::LeftParen  => write!(f, "("),
+            Token::RightParen => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '+' => Token::Op(Op::Add),
+            '-' => Token::Op(Op::Sub),
+            '*' => Token::Op(Op::Mul),
+            '/' => Token::Op(Op::Div),
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_digit() || c == '.' || c.is_alphabetic() => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '.') { break; }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &expr[start..end];
+                if word == "sqrt" {
+                    Token::Op(Op::Sqrt)
+                } else if c.is_alphabetic() {
+                    return Err(Error::InvalidToken(word.to_string()));
+                } else {
+                    let x = word.parse()
+                        .map_err(|_| Error::InvalidToken(word.to_string()))?;
+                    Token::Num(x)
+                }
+            },
+            c => return Err(Error::InvalidToken(c.to_string())),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Evaluates a fully parenthesized infix expression, like
+/// `( 1 + ( ( 2 + 3 ) * ( 4 * 5 ) ) )`, using Dijkstra's two-stack algorithm.
+/// Every operator must be enclosed in its own pair of parentheses.
+pub fn evaluate(expr: &str) -> Result<f64, Error> {
+    let mut ops: Stack<Op> = Stack::new();
+    let mut vals: Stack<f64> = Stack::new();
+    let mut depth = 0;
+    for token in tokenize(expr)? {
+        match token {
+            Token::LeftParen => depth += 1,
+            Token::Op(op)    => ops.push(op),
+            Token::Num(x)    => vals.push(x),
+            Token::RightParen => {
+                if depth == 0 { return Err(Error::UnbalancedParentheses); }
+                depth -= 1;
+                let op = ops.pop().ok_or(Error::MissingOperator)?;
+                op.apply(&mut vals)?;
+            },
+        }
+    }
+    if depth != 0 || !ops.is_empty() {
+        return Err(Error::UnbalancedParentheses);
+    }
+    single_result(vals)
+}
+
+/// Evaluates an infix expression using the usual operator precedence, so
+/// parentheses are only needed to override it: `1 + 2 * 3` is 7, and
+/// `sqrt 16 / 2` is 2.
+pub fn evaluate_infix(expr: &str) -> Result<f64, Error> {
+    let mut vals: Stack<f64> = Stack::new();
+    shunting_yard(&tokenize(expr)?, |token| {
+        match token {
+            Token::Num(x) => { vals.push(x); Ok(()) },
+            Token::Op(op) => op.apply(&mut vals),
+            _             => unreachable!(),
+        }
+    })?;
+    single_result(vals)
+}
+
+/// Evaluates an expression in postfix notation (reverse Polish notation),
+/// like `1 2 3 * +`.
+pub fn evaluate_postfix(expr: &str) -> Result<f64, Error> {
+    let mut vals: Stack<f64> = Stack::new();
+    for token in tokenize(expr)? {
+        match token {
+            Token::Num(x) => vals.push(x),
+            Token::Op(op) => op.apply(&mut vals)?,
+            _ => return Err(Error::UnexpectedToken(token.to_string())),
+        }
+    }
+    single_result(vals)
+}
+
+/// Converts an infix expression, as accepted by `evaluate_infix`, to postfix
+/// notation, with tokens separated by single spaces.
+pub fn infix_to_postfix(expr: &str) -> Result<String, Error> {
+    let mut output: Vec<String> = vec![];
+    shunting_yard(&tokenize(expr)?, |token| {
+        output.push(token.to_string());
+        Ok(())
+    })?;
+    Ok(output.join(" "))
+}
+
+// Dijkstra's shunting-yard algorithm: pass the numbers and operators of an
+// infix expression to `emit` in postfix order.
+fn shunting_yard<F>(tokens: &[Token], mut emit: F) -> Result<(), Error>
+    where F: FnMut(Token) -> Result<(), Error> {
+    // operators and left parentheses
+    let mut ops: Stack<Token> = Stack::new();
+    let mut expect_operand = true;
+    for &token in tokens {
+        let unexpected = Err(Error::UnexpectedToken(token.to_string()));
+        match token {
+            Token::Num(_) => {
+                if !expect_operand { return unexpected; }
+                emit(token)?;
+                expect_operand = false;
+            },
+            Token::LeftParen => {
+                if !expect_operand { return unexpected; }
+                ops.push(token);
+            },
+            Token::Op(op) if op.is_unary() => {
+                if !expect_operand { return unexpected; }
+                ops.push(token);
+            },
+            Token::Op(op) => {
+                if expect_operand { return unexpected; }
+                while let Some(&Token::Op(top)) = ops.peek() {
+                    if top.precedence() < op.precedence() { break; }
+                    emit(ops.pop().unwrap())?;
+                }
+                ops.push(token);
+                expect_operand = true;
+            },
+            Token::RightParen => {
+                if expect_operand { return unexpected; }
+                loop {
+                    match ops.pop() {
+                        Some(Token::LeftParen) => break,
+                        Some(op)               => emit(op)?,
+                        None => return Err(Error::UnbalancedParentheses),
+                    }
+                }
+            },
+        }
+    }
+    if expect_operand {
+        return Err(Error::MissingOperand);
+    }
+    while let Some(token) = ops.pop() {
+        if token == Token::LeftParen {
+            return Err(Error::UnbalancedParentheses);
+        }
+        emit(token)?;
+    }
+    Ok(())
+}
+
+// Return the only value on the stack.
+fn single_result(mut vals: Stack<f64>) -> Result<f64, Error> {
+    let result = vals.pop().ok_or(Error::MissingOperand)?;
+    if !vals.is_empty() {
+        return Err(Error::MissingOperator);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, evaluate, evaluate_infix, evaluate_postfix,
+                infix_to_postfix};
+
+    #[test]
+    fn evaluate_works() {
+        assert_eq!(Ok(101.0), evaluate("( 1 + ( ( 2 + 3 ) * ( 4 * 5 ) ) )"));
+        assert_eq!(Ok(1.618033988749895),
+                   evaluate("( ( 1 + sqrt ( 5.0 ) ) / 2.0 )"));
+        assert_eq!(Ok(42.0), evaluate("42"));
+        assert_eq!(Ok(-1.5), evaluate("((1-2)*1.5)"));
+    }
+
+    #[test]
+    fn evaluate_errors() {
+        assert_eq!(Err(Error::UnbalancedParentheses), evaluate("( 1 + 2"));
+        assert_eq!(Err(Error::UnbalancedParentheses), evaluate("1 + 2 )"));
+        assert_eq!(Err(Error::MissingOperand), evaluate("( 1 + )"));
+        assert_eq!(Err(Error::MissingOperator), evaluate("( 1 2 )"));
+        assert_eq!(Err(Error::MissingOperand), evaluate(""));
+        assert_eq!(Err(Error::InvalidToken("x".to_string())),
+                   evaluate("( 1 + x )"));
+        assert_eq!(Err(Error::InvalidToken("inf".to_string())),
+                   evaluate("inf"));
+        assert_eq!(Err(Error::InvalidToken("%".to_string())),
+                   evaluate("( 1 % 2 )"));
+        assert_eq!(Err(Error::InvalidToken("1.2.3".to_string())),
+                   evaluate("1.2.3"));
+    }
+
+    #[test]
+    fn evaluate_infix_works() {
+        assert_eq!(Ok(7.0), evaluate_infix("1 + 2 * 3"));
+        assert_eq!(Ok(9.0), evaluate_infix("(1 + 2) * 3"));
+        assert_eq!(Ok(2.0), evaluate_infix("8 - 4 - 2"));
+        assert_eq!(Ok(1.0), evaluate_infix("8 / 4 / 2"));
+        assert_eq!(Ok(2.0), evaluate_infix("sqrt 16 / 2"));
+        assert_eq!(Ok(3.0), evaluate_infix("sqrt(4 + 5)"));
+        assert_eq!(Ok(101.0),
+                   evaluate_infix("( 1 + ( ( 2 + 3 ) * ( 4 * 5 ) ) )"));
+    }
+
+    #[test]
+    fn evaluate_infix_errors() {
+        assert_eq!(Err(Error::UnexpectedToken("2".to_string())),
+                   evaluate_infix("1 2"));
+        assert_eq!(Err(Error::UnexpectedToken("*".to_string())),
+                   evaluate_infix("1 + * 2"));
+        assert_eq!(Err(Error::UnexpectedToken(")".to_string())),
+                   evaluate_infix("( )"));
+        assert_eq!(Err(Error::MissingOperand), evaluate_infix("1 +"));
+        assert_eq!(Err(Error::MissingOperand), evaluate_infix(""));
+        assert_eq!(Err(Error::UnbalancedParentheses), evaluate_infix("(1 + 2"));
+        assert_eq!(Err(Error::UnbalancedParentheses), evaluate_infix("1 + 2)"));
+    }
+
+    #[test]
+    fn evaluate_postfix_works() {
+        assert_eq!(Ok(7.0), evaluate_postfix("1 2 3 * +"));
+        assert_eq!(Ok(2.0), evaluate_postfix("8 4 - 2 -"));
+        assert_eq!(Ok(3.0), evaluate_postfix("4 5 + sqrt"));
+        assert_eq!(Err(Error::MissingOperand), evaluate_postfix("1 +"));
+        assert_eq!(Err(Error::MissingOperator), evaluate_postfix("1 2"));
+        assert_eq!(Err(Error::UnexpectedToken("(".to_string())),
+                   evaluate_postfix("( 1 2 + )"));
+    }
+
+    #[test]
+    fn infix_to_postfix_works() {
+        assert_eq!(Ok("1 2 3 * +".to_string()), infix_to_postfix("1+2*3"));
+        assert_eq!(Ok("1 2 + 3 *".to_string()), infix_to_postfix("(1+2)*3"));
+        assert_eq!(Ok("8 4 - 2 -".to_string()), infix_to_postfix("8-4-2"));
+        assert_eq!(Ok("16 sqrt 2 /".to_string()),
+                   infix_to_postfix("sqrt 16 / 2"));
+        assert_eq!(Ok("1 2 3 + 4 5 * * +".to_string()),
+                   infix_to_postfix("( 1 + ( ( 2 + 3 ) * ( 4 * 5 ) ) )"));
+        assert_eq!(Err(Error::MissingOperand), infix_to_postfix("1 * "));
+    }
+
+    #[test]
+    fn infix_and_postfix_agree() {
+        for expr in &["1 + 2 * 3 - 4 / 5", "sqrt(2 * 8) * (3 - 1)",
+                      "((2))", "10 / 4 * 2 - sqrt 9"] {
+            let postfix = infix_to_postfix(expr).unwrap();
+            assert_eq!(evaluate_infix(expr), evaluate_postfix(&postfix));
+        }
+    }
+}
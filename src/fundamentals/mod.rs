@@ -1,5 +1,50 @@
 //! Implementations of data structures and algorithms from Chapter 1:
 //! Fundamentals.
 
+pub mod bag;
+pub mod deque;
+pub mod evaluate;
 pub mod queue;
+pub mod randomized_queue;
+pub mod resizing_array_queue;
+pub mod resizing_array_stack;
 pub mod stack;
+pub mod union_find;
+
+/// Operations shared by the stack implementations, so that algorithms can be
+/// generic over the representation.
+pub trait StackLike<T> {
+    /// Adds an element to the stack.
+    fn push(&mut self, value: T);
+
+    /// Removes the most recently added element and returns it.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns a reference to the most recently added element.
+    fn peek(&self) -> Option<&T>;
+
+    /// Returns `true` if the stack is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns the number of elements in the stack.
+    fn len(&self) -> usize;
+}
+
+/// Operations shared by the queue implementations, so that algorithms can be
+/// generic over the representation.
+pub trait QueueLike<T> {
+    /// Adds an element to the end of the queue.
+    fn enqueue(&mut self, value: T);
+
+    /// Removes the least recently added element and returns it.
+    fn dequeue(&mut self) -> Option<T>;
+
+    /// Returns a reference to the least recently added element.
+    fn peek(&self) -> Option<&T>;
+
+    /// Returns `true` if the queue is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns the number of elements in the queue.
+    fn len(&self) -> usize;
+}
@@ -1,30 +1,48 @@
 //! Algorithm 1.3: FIFO queue.
+//!
+//! A singly linked list with a pointer to its last node can't be expressed
+//! with `Box` links alone, since the last node would have two owners. Instead
+//! the queue keeps two lists: `first` holds the oldest elements, front to
+//! back, and `back` holds the newest ones, most recent first. Elements are
+//! enqueued by pushing them onto `back`, and when `first` runs out, `back` is
+//! reversed to become the new `first`. Each element is moved once, so both
+//! operations take constant amortized time, and no `unsafe` code is needed.
+//! The price is that a single `dequeue` can take time proportional to the
+//! size of the queue, unlike the constant worst case of the book's version,
+//! and that iterating over the newest elements collects them into a vector
+//! first, to visit them in order.
 
-use std::ops::DerefMut;
-use std::ptr;
+use std::fmt;
+use std::iter::FromIterator;
+
+use super::QueueLike;
+
+type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T> {
     value: T,
-    next: Option<Box<Node<T>>>,
+    next: Link<T>,
 }
 
 impl<T> Node<T> {
-    fn new(value: T, next: Option<Box<Node<T>>>) -> Node<T> {
-        Node { value: value, next: next }
+    fn new(value: T, next: Link<T>) -> Node<T> {
+        Node { value, next }
     }
 }
 
 /// A queue implementation based on a linked list.
 pub struct Queue<T> {
-    first: Option<Box<Node<T>>>,
-    last: * mut Node<T>,
+    // the oldest elements, front to back; `None` only if the queue is empty
+    first: Link<T>,
+    // the rest of the elements, back to front
+    back: Link<T>,
     n: usize,
 }
 
 impl<T> Queue<T> {
     /// Constructs a new, empty `Queue<T>`.
     pub fn new() -> Queue<T> {
-        Queue { first: None, last: ptr::null_mut(), n: 0 }
+        Queue { first: None, back: None, n: 0 }
     }
 
     /// Returns `true` if the queue contains no elements.
@@ -39,38 +57,252 @@ impl<T> Queue<T> {
 
     /// Adds an element to the end of the queue.
     pub fn enqueue(&mut self, value: T) {
-        let mut new_element = Box::new(Node::new(value, None));
-        if self.is_empty() {
-            self.last = new_element.deref_mut();
-            self.first = Some(new_element);
+        if self.first.is_none() {
+            self.first = Some(Box::new(Node::new(value, None)));
         } else {
-            let old_last = self.last;
-            self.last = new_element.deref_mut();
-            unsafe { (*old_last).next = Some(new_element); }
+            self.back = Some(Box::new(Node::new(value, self.back.take())));
         }
         self.n += 1;
     }
 
     /// Removes an element from the end of the queue and returns it.
+    ///
+    /// Takes constant amortized time, but time proportional to the number of
+    /// elements enqueued since the last reversal when it reverses them.
     pub fn dequeue(&mut self) -> Option<T> {
         self.first.take().map(|mut node| {
-            self.n -= 1;
-            match node.next.take() {
-                Some(next_node) => {
-                    self.first = Some(next_node);
-                },
-                None => {
-                    self.first = None;
-                    self.last = ptr::null_mut();
-                },
+            self.first = node.next.take();
+            if self.first.is_none() {
+                self.first = reverse(self.back.take());
             }
+            self.n -= 1;
             node.value
         })
     }
+
+    /// Returns a reference to the element at the front of the queue, or
+    /// `None` if the queue is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.first.as_ref().map(|node| &node.value)
+    }
+
+    /// Returns a mutable reference to the element at the front of the queue,
+    /// or `None` if the queue is empty.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.first.as_mut().map(|node| &mut node.value)
+    }
+
+    /// Returns an iterator over the queue, from front to back.
+    ///
+    /// When the iterator reaches the elements enqueued since the last
+    /// reversal, it collects references to them into a vector, so iterating
+    /// can allocate space proportional to the size of the queue.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.first.as_deref(),
+            back: self.back.as_deref(),
+            rest: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator that allows modifying each element, from front to
+    /// back. Like `iter`, it can allocate space proportional to the size of
+    /// the queue.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.first.as_deref_mut(),
+            back: self.back.as_deref_mut(),
+            rest: Vec::new(),
+        }
+    }
+}
+
+// Reverse the list starting at `link`, returning its new first node.
+fn reverse<T>(mut link: Link<T>) -> Link<T> {
+    let mut reversed = None;
+    while let Some(mut node) = link {
+        link = node.next.take();
+        node.next = reversed;
+        reversed = Some(node);
+    }
+    reversed
+}
+
+// Unlink the nodes one by one; the default recursive drop of the `Box` chain
+// overflows the call stack for long lists.
+fn drop_list<T>(mut link: Link<T>) {
+    while let Some(mut node) = link {
+        link = node.next.take();
+    }
+}
+
+impl<T> QueueLike<T> for Queue<T> {
+    fn enqueue(&mut self, value: T) {
+        Queue::enqueue(self, value)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        Queue::dequeue(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        Queue::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Queue::is_empty(self)
+    }
+
+    fn len(&self) -> usize {
+        Queue::len(self)
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        drop_list(self.first.take());
+        drop_list(self.back.take());
+    }
+}
+
+/// Iterator over the elements of a `Queue`.
+pub struct Iter<'a, T: 'a> {
+    next: Option<&'a Node<T>>,
+    // the list of the newest elements, until `next` runs out
+    back: Option<&'a Node<T>>,
+    // the newest elements, back to front, once `back` has been walked
+    rest: Vec<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if let Some(node) = self.next {
+            self.next = node.next.as_deref();
+            return Some(&node.value);
+        }
+        let mut back = self.back.take();
+        while let Some(node) = back {
+            self.rest.push(&node.value);
+            back = node.next.as_deref();
+        }
+        self.rest.pop()
+    }
+}
+
+/// Iterator over mutable references to the elements of a `Queue`.
+pub struct IterMut<'a, T: 'a> {
+    next: Option<&'a mut Node<T>>,
+    back: Option<&'a mut Node<T>>,
+    rest: Vec<&'a mut T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if let Some(node) = self.next.take() {
+            self.next = node.next.as_deref_mut();
+            return Some(&mut node.value);
+        }
+        let mut back = self.back.take();
+        while let Some(node) = back {
+            self.rest.push(&mut node.value);
+            back = node.next.as_deref_mut();
+        }
+        self.rest.pop()
+    }
+}
+
+/// Iterator that moves elements out of a `Queue`, dequeuing them in FIFO
+/// order.
+pub struct IntoIter<T>(Queue<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.dequeue()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.n, Some(self.0.n))
+    }
+}
+
+impl<T> IntoIterator for Queue<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Queue<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Queue<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Queue<T> {
+        let mut q = Queue::new();
+        q.extend(iter);
+        q
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.enqueue(value);
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Queue<T> {
+        Queue::new()
+    }
+}
+
+impl<T: Clone> Clone for Queue<T> {
+    fn clone(&self) -> Queue<T> {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Queue<T> {
+    fn eq(&self, other: &Queue<T>) -> bool {
+        self.n == other.n && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Queue<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
     use super::Queue;
 
     #[test]
@@ -103,4 +335,89 @@ mod tests {
         }
         assert_eq!(q.dequeue(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn peek() {
+        let mut q = Queue::new();
+        assert_eq!(q.peek(), None);
+        q.enqueue(1);
+        q.enqueue(2);
+        assert_eq!(q.peek(), Some(&1));
+        *q.peek_mut().unwrap() = 10;
+        assert_eq!(q.dequeue(), Some(10));
+        assert_eq!(q.peek(), Some(&2));
+        assert_eq!(q.len(), 1);
+    }
+
+    #[test]
+    fn iteration() {
+        let mut q: Queue<isize> = (0..5).collect();
+        assert_eq!(q.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        for x in &mut q {
+            *x *= 10;
+        }
+        let mut seen = vec![];
+        for x in &q {
+            seen.push(*x);
+        }
+        assert_eq!(seen, vec![0, 10, 20, 30, 40]);
+        assert_eq!(q.len(), 5);
+        assert_eq!(q.into_iter().collect::<Vec<_>>(), vec![0, 10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn interleaved_operations() {
+        let mut q = Queue::new();
+        let mut expected = VecDeque::new();
+        for i in 0..100 {
+            q.enqueue(i);
+            expected.push_back(i);
+            if i % 3 == 0 {
+                assert_eq!(q.dequeue(), expected.pop_front());
+            }
+            assert_eq!(q.peek(), expected.front());
+            assert_eq!(q.len(), expected.len());
+            assert!(q.iter().eq(expected.iter()));
+        }
+        for x in q.iter_mut() {
+            *x += 1;
+        }
+        expected.iter_mut().for_each(|x| *x += 1);
+        assert!(q.into_iter().eq(expected.into_iter()));
+    }
+
+    #[test]
+    fn extend_after_dequeue() {
+        let mut q: Queue<isize> = (0..2).collect();
+        q.dequeue();
+        q.dequeue();
+        q.extend(vec![5, 6]);
+        assert_eq!(q.iter().cloned().collect::<Vec<_>>(), vec![5, 6]);
+    }
+
+    #[test]
+    fn clone_eq_and_debug() {
+        let q: Queue<isize> = (0..3).collect();
+        let mut r = q.clone();
+        assert_eq!(q, r);
+        assert_eq!(format!("{:?}", r), "[0, 1, 2]");
+        r.enqueue(3);
+        assert!(q != r);
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Queue<String>>();
+    }
+
+    #[test]
+    fn drop_ten_million_elements() {
+        let mut q = Queue::new();
+        for i in 0..10_000_000 {
+            q.enqueue(i);
+        }
+        assert_eq!(q.len(), 10_000_000);
+        drop(q);
+    }
+}
@@ -0,0 +1,203 @@
+//! Randomized queue (Exercise 1.3.35).
+
+use std::cell::RefCell;
+use std::fmt;
+use std::vec;
+
+use rand::{self, Rng, SeedableRng, StdRng};
+
+/// A queue that removes and samples elements uniformly at random. It's based
+/// on a resizing array: removing an element swaps a random element with the
+/// last one, so every operation takes constant amortized time.
+pub struct RandomizedQueue<T> {
+    a: Vec<T>,
+    // The random number generator sits in a `RefCell` so that `sample` and
+    // `iter` can be used through a shared reference.
+    rng: RefCell<StdRng>,
+}
+
+impl<T> RandomizedQueue<T> {
+    /// Constructs a new, empty queue using a randomly seeded generator.
+    pub fn new() -> RandomizedQueue<T> {
+        let seed: usize = rand::thread_rng().gen();
+        RandomizedQueue::with_seed(seed)
+    }
+
+    /// Constructs a new, empty queue whose random choices are determined by
+    /// `seed`, so results are reproducible.
+    pub fn with_seed(seed: usize) -> RandomizedQueue<T> {
+        let seed: &[_] = &[seed];
+        RandomizedQueue {
+            a: vec![],
+            rng: RefCell::new(SeedableRng::from_seed(seed)),
+        }
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Adds an element to the queue.
+    pub fn enqueue(&mut self, value: T) {
+        self.a.push(value);
+    }
+
+    /// Removes an element chosen uniformly at random and returns it.
+    pub fn dequeue(&mut self) -> Option<T> {
+        if self.a.is_empty() { return None; }
+        let n = self.a.len();
+        let i = self.rng.get_mut().gen_range(0, n);
+        Some(self.a.swap_remove(i))
+    }
+
+    /// Returns an element chosen uniformly at random without removing it.
+    pub fn sample(&self) -> Option<&T> {
+        if self.a.is_empty() { return None; }
+        let i = self.rng.borrow_mut().gen_range(0, self.a.len());
+        Some(&self.a[i])
+    }
+
+    /// Returns an iterator over the elements in uniformly random order. Each
+    /// iterator picks its own order, independently of any other iterator.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut order: Vec<usize> = (0..self.a.len()).collect();
+        self.rng.borrow_mut().shuffle(&mut order);
+        Iter { a: &self.a, order: order.into_iter() }
+    }
+}
+
+/// Iterator over the elements of a `RandomizedQueue`, in random order.
+pub struct Iter<'a, T: 'a> {
+    a: &'a [T],
+    order: vec::IntoIter<usize>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.order.next().map(|i| &self.a[i])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+/// Moves the elements out of the queue in random order.
+impl<T> IntoIterator for RandomizedQueue<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        let mut a = self.a;
+        self.rng.borrow_mut().shuffle(&mut a);
+        a.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RandomizedQueue<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for RandomizedQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.a.extend(iter);
+    }
+}
+
+impl<T> Default for RandomizedQueue<T> {
+    fn default() -> RandomizedQueue<T> {
+        RandomizedQueue::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RandomizedQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RandomizedQueue").field("a", &self.a).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomizedQueue;
+
+    fn queue_0_to_n(n: usize, seed: usize) -> RandomizedQueue<usize> {
+        let mut q = RandomizedQueue::with_seed(seed);
+        q.extend(0..n);
+        q
+    }
+
+    #[test]
+    fn empty_queue() {
+        let mut q: RandomizedQueue<isize> = RandomizedQueue::new();
+        assert!(q.is_empty());
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.sample(), None);
+        assert_eq!(q.dequeue(), None);
+        assert_eq!(q.iter().next(), None);
+    }
+
+    #[test]
+    fn dequeue_returns_every_element_once() {
+        let mut q = queue_0_to_n(100, 1);
+        let mut seen = vec![];
+        while let Some(x) = q.dequeue() {
+            seen.push(x);
+        }
+        assert!(seen != (0..100).collect::<Vec<_>>());
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn same_seed_same_order() {
+        let mut q1 = queue_0_to_n(20, 42);
+        let mut q2 = queue_0_to_n(20, 42);
+        for _ in 0..20 {
+            assert_eq!(q1.dequeue(), q2.dequeue());
+        }
+    }
+
+    #[test]
+    fn iterators_are_independent() {
+        let q = queue_0_to_n(50, 7);
+        let mut it1 = q.iter();
+        let mut it2 = q.iter();
+        let mut v1 = vec![];
+        let mut v2 = vec![];
+        for _ in 0..50 {
+            v1.push(*it1.next().unwrap());
+            v2.push(*it2.next().unwrap());
+        }
+        assert_eq!(it1.next(), None);
+        assert!(v1 != v2);
+        v1.sort();
+        v2.sort();
+        assert_eq!(v1, v2);
+        assert_eq!(q.len(), 50);
+    }
+
+    #[test]
+    fn sample_is_roughly_uniform() {
+        let q = queue_0_to_n(4, 3);
+        let mut counts = [0; 4];
+        for _ in 0..4000 {
+            counts[*q.sample().unwrap()] += 1;
+        }
+        for &c in &counts {
+            assert!(c > 800 && c < 1200);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! FIFO queue (resizing array implementation, Exercise 1.3.14).
+
+use std::iter::FromIterator;
+
+use super::QueueLike;
+use super::deque::{self, Deque};
+
+/// A queue implementation based on a resizing circular array. The elements are
+/// stored contiguously (up to one wrap-around), which makes it much more cache
+/// friendly than the linked-list `Queue`.
+pub struct ResizingArrayQueue<T> {
+    a: Deque<T>,
+}
+
+impl<T> ResizingArrayQueue<T> {
+    /// Constructs a new, empty queue.
+    pub fn new() -> ResizingArrayQueue<T> {
+        ResizingArrayQueue { a: Deque::new() }
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Adds an element to the end of the queue.
+    pub fn enqueue(&mut self, value: T) {
+        self.a.push_back(value);
+    }
+
+    /// Removes the element at the front of the queue and returns it.
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.a.pop_front()
+    }
+
+    /// Returns a reference to the element at the front of the queue, or
+    /// `None` if the queue is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.a.peek_front()
+    }
+
+    /// Returns an iterator over the queue, from front to back.
+    pub fn iter(&self) -> deque::Iter<'_, T> {
+        self.a.iter()
+    }
+}
+
+impl<T> QueueLike<T> for ResizingArrayQueue<T> {
+    fn enqueue(&mut self, value: T) {
+        ResizingArrayQueue::enqueue(self, value)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        ResizingArrayQueue::dequeue(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        ResizingArrayQueue::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ResizingArrayQueue::is_empty(self)
+    }
+
+    fn len(&self) -> usize {
+        ResizingArrayQueue::len(self)
+    }
+}
+
+impl<T> IntoIterator for ResizingArrayQueue<T> {
+    type Item = T;
+    type IntoIter = deque::IntoIter<T>;
+
+    fn into_iter(self) -> deque::IntoIter<T> {
+        self.a.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ResizingArrayQueue<T> {
+    type Item = &'a T;
+    type IntoIter = deque::Iter<'a, T>;
+
+    fn into_iter(self) -> deque::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for ResizingArrayQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ResizingArrayQueue<T> {
+        ResizingArrayQueue { a: iter.into_iter().collect() }
+    }
+}
+
+impl<T> Extend<T> for ResizingArrayQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.a.extend(iter);
+    }
+}
+
+impl<T> Default for ResizingArrayQueue<T> {
+    fn default() -> ResizingArrayQueue<T> {
+        ResizingArrayQueue::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResizingArrayQueue;
+    use super::super::QueueLike;
+    use super::super::queue::Queue;
+
+    #[test]
+    fn empty_queue() {
+        let mut q: ResizingArrayQueue<isize> = ResizingArrayQueue::new();
+        assert!(q.is_empty());
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.dequeue(), None);
+    }
+
+    #[test]
+    fn ten_elements() {
+        let mut q = ResizingArrayQueue::new();
+        for i in 0..10 {
+            q.enqueue(i);
+        }
+        assert_eq!(q.peek(), Some(&0));
+        assert_eq!(q.iter().cloned().collect::<Vec<_>>(),
+                   (0..10).collect::<Vec<_>>());
+        for i in 0..10 {
+            assert_eq!(q.dequeue(), Some(i));
+        }
+        assert_eq!(q.dequeue(), None);
+    }
+
+    // Interleaves enqueue and dequeue operations on any queue.
+    fn interleave<Q: QueueLike<isize>>(mut q: Q) -> Vec<isize> {
+        let mut output = vec![];
+        for i in 0..100 {
+            q.enqueue(i);
+            if i % 3 == 0 {
+                output.push(q.dequeue().unwrap());
+            }
+        }
+        assert_eq!(q.peek(), Some(&34));
+        while let Some(x) = q.dequeue() {
+            output.push(x);
+        }
+        assert!(q.is_empty());
+        assert_eq!(q.len(), 0);
+        output
+    }
+
+    #[test]
+    fn generic_over_queue_like() {
+        assert_eq!(interleave(Queue::new()),
+                   interleave(ResizingArrayQueue::new()));
+    }
+}
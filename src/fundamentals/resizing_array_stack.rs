@@ -0,0 +1,204 @@
+//! Algorithm 1.1: Pushdown (LIFO) stack (resizing array implementation).
+
+use std::iter::{FromIterator, Rev};
+use std::slice;
+use std::vec;
+
+use super::StackLike;
+
+/// A stack implementation based on an array that doubles in size when it's
+/// full and halves when it's one-quarter full.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResizingArrayStack<T> {
+    a: Vec<T>,
+}
+
+impl<T> ResizingArrayStack<T> {
+    /// Constructs a new, empty stack.
+    pub fn new() -> ResizingArrayStack<T> {
+        ResizingArrayStack { a: vec![] }
+    }
+
+    /// Returns `true` if the stack is empty.
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+
+    /// Returns the number of elements in the stack.
+    pub fn size(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Adds an element to the stack.
+    pub fn push(&mut self, value: T) {
+        if self.a.len() == self.a.capacity() {
+            let n = self.a.len();
+            self.a.reserve_exact(if n == 0 { 1 } else { n });
+        }
+        self.a.push(value);
+    }
+
+    /// Removes an element from the stack and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.a.pop();
+        let n = self.a.len();
+        if n > 0 && n == self.a.capacity() / 4 {
+            let cap = self.a.capacity() / 2;
+            self.a.shrink_to(cap);
+        }
+        value
+    }
+
+    /// Returns a reference to the most recently added element, or `None` if
+    /// the stack is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.a.last()
+    }
+
+    /// Returns a mutable reference to the most recently added element, or
+    /// `None` if the stack is empty.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.a.last_mut()
+    }
+
+    /// Returns an iterator over the stack, from the most recently added
+    /// element to the least recently added one.
+    pub fn iter(&self) -> Rev<slice::Iter<'_, T>> {
+        self.a.iter().rev()
+    }
+
+    /// Returns an iterator that allows modifying each element, in the same
+    /// order as `iter`.
+    pub fn iter_mut(&mut self) -> Rev<slice::IterMut<'_, T>> {
+        self.a.iter_mut().rev()
+    }
+}
+
+impl<T> StackLike<T> for ResizingArrayStack<T> {
+    fn push(&mut self, value: T) {
+        ResizingArrayStack::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        ResizingArrayStack::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        ResizingArrayStack::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ResizingArrayStack::is_empty(self)
+    }
+
+    fn len(&self) -> usize {
+        self.size()
+    }
+}
+
+/// Moves the elements out of the stack in LIFO order.
+impl<T> IntoIterator for ResizingArrayStack<T> {
+    type Item = T;
+    type IntoIter = Rev<vec::IntoIter<T>>;
+
+    fn into_iter(self) -> Rev<vec::IntoIter<T>> {
+        self.a.into_iter().rev()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ResizingArrayStack<T> {
+    type Item = &'a T;
+    type IntoIter = Rev<slice::Iter<'a, T>>;
+
+    fn into_iter(self) -> Rev<slice::Iter<'a, T>> {
+        self.iter()
+    }
+}
+
+/// Pushes the elements in iteration order, so the last one ends up on top.
+impl<T> FromIterator<T> for ResizingArrayStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ResizingArrayStack<T> {
+        let mut s = ResizingArrayStack::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<T> Extend<T> for ResizingArrayStack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> Default for ResizingArrayStack<T> {
+    fn default() -> ResizingArrayStack<T> {
+        ResizingArrayStack::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResizingArrayStack;
+    use super::super::StackLike;
+    use super::super::stack::Stack;
+
+    #[test]
+    fn empty_stack() {
+        let mut s: ResizingArrayStack<isize> = ResizingArrayStack::new();
+        assert!(s.is_empty());
+        assert_eq!(s.size(), 0);
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn ten_elements() {
+        let mut s = ResizingArrayStack::new();
+        for i in 0..10 {
+            s.push(i);
+        }
+        assert_eq!(s.peek(), Some(&9));
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(),
+                   vec![9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        for i in 0..10 {
+            assert_eq!(s.pop(), Some(9 - i));
+        }
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn capacity_grows_and_shrinks() {
+        let mut s = ResizingArrayStack::new();
+        for i in 0..64 {
+            s.push(i);
+        }
+        assert_eq!(s.a.capacity(), 64);
+        while s.size() > 15 {
+            s.pop();
+        }
+        assert!(s.a.capacity() < 64);
+        assert!(s.a.capacity() >= 15);
+    }
+
+    // Reverses the elements of `input` using any stack.
+    fn reverse<S: StackLike<isize>>(mut s: S, input: &[isize]) -> Vec<isize> {
+        for &x in input {
+            s.push(x);
+        }
+        assert_eq!(s.len(), input.len());
+        let mut output = vec![];
+        while let Some(x) = s.pop() {
+            output.push(x);
+        }
+        assert!(s.is_empty());
+        output
+    }
+
+    #[test]
+    fn generic_over_stack_like() {
+        let input = [3, 1, 4, 1, 5];
+        assert_eq!(reverse(Stack::new(), &input),
+                   reverse(ResizingArrayStack::new(), &input));
+    }
+}
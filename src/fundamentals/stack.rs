@@ -1,5 +1,10 @@
 //! Algorithm 1.2: Pushdown stack.
 
+use std::fmt;
+use std::iter::FromIterator;
+
+use super::StackLike;
+
 struct Node<T> {
     value: T,
     next: Option<Box<Node<T>>>,
@@ -47,6 +52,181 @@ impl <T> Stack<T> {
             node.value
         })
     }
+
+    /// Returns a reference to the most recently added element, or `None` if
+    /// the stack is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.first.as_ref().map(|node| &node.value)
+    }
+
+    /// Returns a mutable reference to the most recently added element, or
+    /// `None` if the stack is empty.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.first.as_mut().map(|node| &mut node.value)
+    }
+
+    /// Returns an iterator over the stack, from the most recently added
+    /// element to the least recently added one.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.first.as_deref() }
+    }
+
+    /// Returns an iterator that allows modifying each element, in the same
+    /// order as `iter`.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { next: self.first.as_deref_mut() }
+    }
+}
+
+impl<T> StackLike<T> for Stack<T> {
+    fn push(&mut self, value: T) {
+        Stack::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Stack::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        Stack::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Stack::is_empty(self)
+    }
+
+    fn len(&self) -> usize {
+        self.size()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    // Unlink the nodes one by one; the default recursive drop of the `Box`
+    // chain overflows the call stack for long lists.
+    fn drop(&mut self) {
+        let mut link = self.first.take();
+        while let Some(mut node) = link {
+            link = node.next.take();
+        }
+    }
+}
+
+/// Iterator over the elements of a `Stack`.
+pub struct Iter<'a, T: 'a> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+/// Iterator over mutable references to the elements of a `Stack`.
+pub struct IterMut<'a, T: 'a> {
+    next: Option<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.take().map(|node| {
+            self.next = node.next.as_deref_mut();
+            &mut node.value
+        })
+    }
+}
+
+/// Iterator that moves elements out of a `Stack`, popping them in LIFO order.
+pub struct IntoIter<T>(Stack<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.n, Some(self.0.n))
+    }
+}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Stack<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Pushes the elements in iteration order, so the last one ends up on top.
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Stack<T> {
+        let mut s = Stack::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<T> Extend<T> for Stack<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Stack<T> {
+        Stack::new()
+    }
+}
+
+impl<T: Clone> Clone for Stack<T> {
+    fn clone(&self) -> Stack<T> {
+        let values: Vec<&T> = self.iter().collect();
+        values.into_iter().rev().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Stack<T> {
+    fn eq(&self, other: &Stack<T>) -> bool {
+        self.n == other.n && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Stack<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
 #[cfg(test)]
@@ -84,4 +264,62 @@ mod tests {
         }
         assert_eq!(s.pop(), None);
     }
+
+    #[test]
+    fn peek() {
+        let mut s = Stack::new();
+        assert_eq!(s.peek(), None);
+        s.push(1);
+        s.push(2);
+        assert_eq!(s.peek(), Some(&2));
+        *s.peek_mut().unwrap() = 20;
+        assert_eq!(s.pop(), Some(20));
+        assert_eq!(s.peek(), Some(&1));
+        assert_eq!(s.size(), 1);
+    }
+
+    #[test]
+    fn iteration() {
+        let mut s: Stack<isize> = (0..5).collect();
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+        for x in &mut s {
+            *x *= 10;
+        }
+        let mut seen = vec![];
+        for x in &s {
+            seen.push(*x);
+        }
+        assert_eq!(seen, vec![40, 30, 20, 10, 0]);
+        assert_eq!(s.size(), 5);
+        assert_eq!(s.into_iter().collect::<Vec<_>>(), vec![40, 30, 20, 10, 0]);
+    }
+
+    #[test]
+    fn extend() {
+        let mut s = Stack::new();
+        s.push(1);
+        s.extend(vec![2, 3]);
+        assert_eq!(s.size(), 3);
+        assert_eq!(s.pop(), Some(3));
+    }
+
+    #[test]
+    fn clone_eq_and_debug() {
+        let s: Stack<isize> = (0..3).collect();
+        let t = s.clone();
+        assert_eq!(s, t);
+        assert_eq!(format!("{:?}", t), "[2, 1, 0]");
+        let u: Stack<isize> = (0..4).collect();
+        assert!(s != u);
+    }
+
+    #[test]
+    fn drop_ten_million_elements() {
+        let mut s = Stack::new();
+        for i in 0..10_000_000 {
+            s.push(i);
+        }
+        assert_eq!(s.size(), 10_000_000);
+        drop(s);
+    }
 }
@@ -0,0 +1,271 @@
+//! Algorithm 1.5: Union-find.
+//!
+//! Four implementations of the union-find data type for dynamic connectivity:
+//! quick-find, quick-union, weighted quick-union and weighted quick-union with
+//! path compression.
+
+/// The union-find data type: a set of `n` sites, numbered `0` to `n-1`, that
+/// are partitioned into components.
+pub trait UnionFind {
+    /// Adds a connection between `p` and `q`.
+    fn union(&mut self, p: usize, q: usize);
+
+    /// Returns the component identifier for `p`.
+    fn find(&mut self, p: usize) -> usize;
+
+    /// Returns `true` if `p` and `q` are in the same component.
+    fn connected(&mut self, p: usize, q: usize) -> bool {
+        self.find(p) == self.find(q)
+    }
+
+    /// Returns the number of components.
+    fn count(&self) -> usize;
+}
+
+/// Quick-find: `find` is constant-time, `union` is linear.
+pub struct QuickFind {
+    id: Vec<usize>,
+    count: usize,
+}
+
+impl QuickFind {
+    /// Constructs a new union-find structure with `n` sites and no
+    /// connections.
+    pub fn new(n: usize) -> QuickFind {
+        QuickFind { id: (0..n).collect(), count: n }
+    }
+}
+
+impl UnionFind for QuickFind {
+    fn union(&mut self, p: usize, q: usize) {
+        let p_id = self.id[p];
+        let q_id = self.id[q];
+        if p_id == q_id { return; }
+        for id in self.id.iter_mut() {
+            if *id == p_id { *id = q_id; }
+        }
+        self.count -= 1;
+    }
+
+    fn find(&mut self, p: usize) -> usize {
+        self.id[p]
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Quick-union: sites are nodes in a forest and `find` follows parent links to
+/// the root.
+pub struct QuickUnion {
+    parent: Vec<usize>,
+    count: usize,
+}
+
+impl QuickUnion {
+    /// Constructs a new union-find structure with `n` sites and no
+    /// connections.
+    pub fn new(n: usize) -> QuickUnion {
+        QuickUnion { parent: (0..n).collect(), count: n }
+    }
+}
+
+impl UnionFind for QuickUnion {
+    fn union(&mut self, p: usize, q: usize) {
+        let p_root = self.find(p);
+        let q_root = self.find(q);
+        if p_root == q_root { return; }
+        self.parent[p_root] = q_root;
+        self.count -= 1;
+    }
+
+    fn find(&mut self, p: usize) -> usize {
+        root(&self.parent, p)
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Weighted quick-union: always links the root of the smaller tree to the root
+/// of the larger tree, so trees have logarithmic height.
+pub struct WeightedQuickUnion {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl WeightedQuickUnion {
+    /// Constructs a new union-find structure with `n` sites and no
+    /// connections.
+    pub fn new(n: usize) -> WeightedQuickUnion {
+        WeightedQuickUnion {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            count: n,
+        }
+    }
+}
+
+impl UnionFind for WeightedQuickUnion {
+    fn union(&mut self, p: usize, q: usize) {
+        let p_root = self.find(p);
+        let q_root = self.find(q);
+        if p_root == q_root { return; }
+        link(&mut self.parent, &mut self.size, p_root, q_root);
+        self.count -= 1;
+    }
+
+    fn find(&mut self, p: usize) -> usize {
+        root(&self.parent, p)
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Weighted quick-union with path compression: like `WeightedQuickUnion`, but
+/// `find` also links every site it visits directly to the root.
+pub struct PathCompression {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl PathCompression {
+    /// Constructs a new union-find structure with `n` sites and no
+    /// connections.
+    pub fn new(n: usize) -> PathCompression {
+        PathCompression {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            count: n,
+        }
+    }
+}
+
+impl UnionFind for PathCompression {
+    fn union(&mut self, p: usize, q: usize) {
+        let p_root = self.find(p);
+        let q_root = self.find(q);
+        if p_root == q_root { return; }
+        link(&mut self.parent, &mut self.size, p_root, q_root);
+        self.count -= 1;
+    }
+
+    fn find(&mut self, p: usize) -> usize {
+        let r = root(&self.parent, p);
+        let mut p = p;
+        while p != r {
+            let next = self.parent[p];
+            self.parent[p] = r;
+            p = next;
+        }
+        r
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+}
+
+// Follow parent links from `p` to the root of its tree.
+fn root(parent: &[usize], p: usize) -> usize {
+    let mut p = p;
+    while p != parent[p] {
+        p = parent[p];
+    }
+    p
+}
+
+// Link roots `p` and `q`, making the smaller tree a subtree of the larger one.
+fn link(parent: &mut [usize], size: &mut [usize], p: usize, q: usize) {
+    if size[p] < size[q] {
+        parent[p] = q;
+        size[q] += size[p];
+    } else {
+        parent[q] = p;
+        size[p] += size[q];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, StdRng};
+    use graphs::undirected::Graph;
+    use graphs::undirected::cc::ConnectedComponents;
+    use super::{PathCompression, QuickFind, QuickUnion, UnionFind,
+                WeightedQuickUnion};
+
+    // Edges from the book's tinyUF.txt (page 229).
+    fn tiny_edges() -> Vec<(usize, usize)> {
+        vec![(4,3), (3,8), (6,5), (9,4), (2,1),
+             (8,9), (5,0), (7,2), (6,1), (1,0), (6,7)]
+    }
+
+    fn random_edges(seed: usize, n: usize, m: usize) -> Vec<(usize, usize)> {
+        let seed: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        (0..m).map(|_| (rng.gen_range(0, n), rng.gen_range(0, n))).collect()
+    }
+
+    fn check_tiny<U: UnionFind>(mut uf: U) {
+        assert_eq!(10, uf.count());
+        for &(p, q) in &tiny_edges() {
+            uf.union(p, q);
+        }
+        assert_eq!(2, uf.count());
+        assert!(uf.connected(0, 7));
+        assert!(uf.connected(3, 9));
+        assert!( ! uf.connected(0, 3));
+        assert_eq!(uf.find(1), uf.find(6));
+    }
+
+    fn check_against_cc<U: UnionFind>(mut uf: U,
+                                      n: usize,
+                                      edges: &[(usize, usize)]) {
+        let mut g = Graph::new(n);
+        for &(p, q) in edges {
+            g.add_edge(p, q);
+            uf.union(p, q);
+        }
+        let cc = ConnectedComponents::find(&g);
+        assert_eq!(cc.count(), uf.count());
+        for p in 0..n {
+            for q in 0..n {
+                assert_eq!(cc.connected(p, q), uf.connected(p, q));
+            }
+        }
+    }
+
+    #[test]
+    fn tiny_example() {
+        check_tiny(QuickFind::new(10));
+        check_tiny(QuickUnion::new(10));
+        check_tiny(WeightedQuickUnion::new(10));
+        check_tiny(PathCompression::new(10));
+    }
+
+    #[test]
+    fn no_connections() {
+        let mut uf = PathCompression::new(3);
+        assert_eq!(3, uf.count());
+        assert!( ! uf.connected(0, 1));
+        assert!(uf.connected(2, 2));
+    }
+
+    #[test]
+    fn agrees_with_connected_components() {
+        let n = 50;
+        for seed in 1..6 {
+            let edges = random_edges(seed, n, seed * 10);
+            check_against_cc(QuickFind::new(n), n, &edges);
+            check_against_cc(QuickUnion::new(n), n, &edges);
+            check_against_cc(WeightedQuickUnion::new(n), n, &edges);
+            check_against_cc(PathCompression::new(n), n, &edges);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Algorithm 4.2: Breadth-first search to find paths in an undirected graph.
 
+use fundamentals::QueueLike;
+use fundamentals::queue::Queue;
 use super::Graph;
 
 pub struct BreadthFirstSearch {
@@ -12,27 +14,31 @@ impl BreadthFirstSearch {
 
     /// Breadth-first search to find paths from `s` to nodes connected to `s`.
     pub fn new(g: &Graph, s: usize) -> BreadthFirstSearch {
+        BreadthFirstSearch::with_queue(g, s, Queue::new())
+    }
+
+    /// Like `new`, but uses `queue` to hold the vertices waiting to be
+    /// visited. The queue should be empty.
+    pub fn with_queue<Q>(g: &Graph, s: usize, queue: Q) -> BreadthFirstSearch
+        where Q: QueueLike<usize> {
         let mut d = BreadthFirstSearch {
-            marked: vec![false; g.v],
-            edge_to: vec![0; g.v],
+            marked: vec![false; g.v()],
+            edge_to: vec![0; g.v()],
             s: s,
         };
-        d.bfs(g, s);
+        d.bfs(g, s, queue);
         d
     }
 
-    fn bfs(&mut self, g: &Graph, s: usize) {
-        let mut queue = Vec::new();
+    fn bfs<Q: QueueLike<usize>>(&mut self, g: &Graph, s: usize, mut queue: Q) {
         self.marked[s] = true;
-        queue.push(s);
-        while !queue.is_empty() {
-            let v = queue.remove(0);
-            let neighbors = g.adj[v].clone();
-            for w in neighbors {
+        queue.enqueue(s);
+        while let Some(v) = queue.dequeue() {
+            for w in g.adj(v) {
                 if !self.marked[w] {
                     self.edge_to[w] = v;
                     self.marked[w] = true;
-                    queue.push(w);
+                    queue.enqueue(w);
                 }
             }
         }
@@ -55,6 +61,7 @@ impl BreadthFirstSearch {
 
 #[cfg(test)]
 mod test {
+    use fundamentals::resizing_array_queue::ResizingArrayQueue;
     use super::BreadthFirstSearch;
     use super::super::test::sample_graph;
 
@@ -96,5 +103,14 @@ mod test {
         assert!(p3 == vec![0,5,3]);
     }
 
-}
+    #[test]
+    fn with_resizing_array_queue() {
+        let g = sample_graph();
+        let d1 = BreadthFirstSearch::new(&g, 0);
+        let d2 = BreadthFirstSearch::with_queue(&g, 0,
+                                                ResizingArrayQueue::new());
+        assert_eq!(d1.marked, d2.marked);
+        assert_eq!(d1.edge_to, d2.edge_to);
+    }
 
+}
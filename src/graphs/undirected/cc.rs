@@ -12,11 +12,11 @@ impl ConnectedComponents {
     /// Find connected components with depth-first search.
     pub fn find(g: &Graph) -> ConnectedComponents {
         let mut cc = ConnectedComponents {
-            id: vec![0; g.v],
+            id: vec![0; g.v()],
             count: 0,
         };
-        let mut marked = vec![false; g.v];
-        for v in 0..g.v {
+        let mut marked = vec![false; g.v()];
+        for v in 0..g.v() {
             if ! marked[v] {
                 cc.dfs(g, &mut marked, v);
                 cc.count += 1;
@@ -28,8 +28,7 @@ impl ConnectedComponents {
     fn dfs(&mut self, g: &Graph, marked: &mut Vec<bool>, v: usize) {
         marked[v] = true;
         self.id[v] = self.count;
-        let neighbors = g.adj[v].clone();
-        for w in neighbors {
+        for w in g.adj(v) {
             if ! marked[w] {
                 self.dfs(g, marked, w);
             }
@@ -41,6 +40,11 @@ impl ConnectedComponents {
         return self.id[v] == self.id[w];
     }
 
+    /// Returns the number of connected components.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
 }
 
 #[cfg(test)]
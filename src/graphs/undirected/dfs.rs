@@ -13,8 +13,8 @@ impl DepthFirstSearch {
     /// Depth-first search to find paths from `s` to nodes connected to `s`.
     pub fn new(g: &Graph, s: usize) -> DepthFirstSearch {
         let mut d = DepthFirstSearch {
-            marked: vec![false; g.v],
-            edge_to: vec![0; g.v],
+            marked: vec![false; g.v()],
+            edge_to: vec![0; g.v()],
             s: s,
         };
         d.dfs(g, s);
@@ -23,8 +23,7 @@ impl DepthFirstSearch {
 
     fn dfs(&mut self, g: &Graph, v: usize) {
         self.marked[v] = true;
-        let neighbors = g.adj[v].clone();
-        for w in neighbors {
+        for w in g.adj(v) {
             if !self.marked[w] {
                 self.edge_to[w] = v;
                 self.dfs(g, w);
@@ -89,7 +88,8 @@ mod test {
         let p3 = d.path_to(3);
         assert!(p3 == vec![0,5,3]
              || p3 == vec![0,5,4,3]
-             || p3 == vec![0,6,4,3]);
+             || p3 == vec![0,6,4,3]
+             || p3 == vec![0,6,4,5,3]);
     }
 
 }
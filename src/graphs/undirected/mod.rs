@@ -5,6 +5,8 @@ pub mod bfs;
 pub mod cc;
 pub mod dfs;
 
+use fundamentals::bag::{self, Bag};
+
 /// Data structure for an undirected graph.
 pub struct Graph {
     /// number of vertices
@@ -12,25 +14,58 @@ pub struct Graph {
     /// number of edges
     e: usize,
     /// adjacency lists
-    adj: Vec<Vec<usize>>,
+    adj: Vec<Bag<usize>>,
 }
 
 impl Graph {
     /// Constructs a new, empty graph.
     pub fn new(v: usize) -> Graph {
-        Graph { v: v, e: 0, adj: vec![vec![]; v] }
+        Graph { v, e: 0, adj: (0..v).map(|_| Bag::new()).collect() }
+    }
+
+    /// Returns the number of vertices.
+    pub fn v(&self) -> usize {
+        self.v
+    }
+
+    /// Returns the number of edges.
+    pub fn e(&self) -> usize {
+        self.e
     }
 
     /// Add edge v-w to the graph.
     pub fn add_edge(&mut self, v: usize, w: usize) {
         assert!(v < self.v);
         assert!(w < self.v);
-        self.adj[v].push(w);
-        self.adj[w].push(v);
+        self.adj[v].add(w);
+        self.adj[w].add(v);
         self.e += 1;
     }
+
+    /// Returns an iterator over the vertices adjacent to `v`.
+    pub fn adj(&self, v: usize) -> Adj<'_> {
+        Adj(self.adj[v].iter())
+    }
 }
 
+/// Iterator over the vertices adjacent to a vertex, returned by `Graph::adj`.
+#[derive(Clone)]
+pub struct Adj<'a>(bag::Iter<'a, usize>);
+
+impl<'a> Iterator for Adj<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.0.next().cloned()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Adj<'a> {}
+
 #[cfg(test)]
 mod test {
     use super::Graph;
@@ -74,5 +109,16 @@ mod test {
         assert_eq!(13, g.v);
         assert_eq!(13, g.e);
     }
+
+    #[test]
+    fn adj() {
+        let g = sample_graph();
+        assert_eq!(13, g.v());
+        assert_eq!(13, g.e());
+        let mut adj: Vec<usize> = g.adj(0).collect();
+        adj.sort();
+        assert_eq!(vec![1, 2, 5, 6], adj);
+        assert_eq!(2, g.adj(12).len());
+    }
 }
 
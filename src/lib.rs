@@ -1,7 +1,10 @@
 //! Implementations of data structures and algorithms from the book
 //! *Algorithms*, fourth edition, by Robert Sedgewick and Kevin Wayne.
 
+extern crate rand;
+
 pub mod fundamentals;
 pub mod graphs;
 pub mod searching;
 pub mod sorting;
+pub mod strings;
@@ -0,0 +1,389 @@
+//! External merge sort, for inputs larger than memory.
+//!
+//! The input is read in runs that fit in the memory budget. Each run is
+//! sorted in memory with introsort and written to a temporary file, and the
+//! runs are then merged with an indexed priority queue, as in `Multiway`. If
+//! there are more runs than can be merged at once, they're merged in several
+//! passes. An input that fits in a single run is sorted without temporary
+//! files.
+//!
+//! Records are either lines, ending with `'\n'`, or binary records of a fixed
+//! size, and are compared as byte strings.
+
+use std::cmp;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::heap::index_pq::IndexMinPQ;
+use super::intro;
+
+// The most runs that are merged at once.
+const MAX_FAN_IN: usize = 64;
+
+// The smallest buffer for reading a run that's worth merging it with others.
+const MIN_BUFFER: usize = 4096;
+
+/// The resources an external sort may use.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// the approximate number of bytes of memory to hold records in
+    pub memory: usize,
+    /// the directory in which to create temporary files
+    pub temp_dir: PathBuf,
+}
+
+impl Options {
+    /// Constructs `Options` with a memory budget of `memory` bytes and the
+    /// system's temporary directory.
+    pub fn with_memory(memory: usize) -> Options {
+        Options { memory, temp_dir: env::temp_dir() }
+    }
+}
+
+impl Default for Options {
+    /// A memory budget of 64 MiB and the system's temporary directory.
+    fn default() -> Options {
+        Options::with_memory(64 << 20)
+    }
+}
+
+/// What an external sort did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// number of records sorted
+    pub records: usize,
+    /// number of sorted runs the input was split into
+    pub runs: usize,
+    /// number of merge passes over the records
+    pub passes: usize,
+}
+
+/// Sort the lines of `input` in byte order and write them to `output`. Every
+/// line of the output ends with `'\n'`, including the last one.
+pub fn sort_lines<R: Read, W: Write>(input: R, output: W, options: &Options)
+    -> io::Result<Report> {
+    sort_format(input, output, Format::Lines, options)
+}
+
+/// Sort the records of `record_size` bytes in `input` in byte order and write
+/// them to `output`. Returns an error of kind `InvalidData` if the length of
+/// `input` isn't a multiple of `record_size`.
+///
+/// # Panics
+///
+/// Panics if `record_size` is 0.
+pub fn sort_records<R: Read, W: Write>(input: R, output: W,
+                                       record_size: usize, options: &Options)
+    -> io::Result<Report> {
+    assert!(record_size > 0, "records must be at least 1 byte long");
+    sort_format(input, output, Format::Fixed(record_size), options)
+}
+
+// How records are stored in the input, the temporary files and the output.
+#[derive(Clone, Copy)]
+enum Format {
+    Lines,
+    Fixed(usize),
+}
+
+impl Format {
+    // Read the next record, or return `None` at the end of the input.
+    fn read<R: BufRead>(self, r: &mut R) -> io::Result<Option<Vec<u8>>> {
+        match self {
+            Format::Lines => {
+                let mut line = vec![];
+                if r.read_until(b'\n', &mut line)? == 0 { return Ok(None); }
+                if line.last() == Some(&b'\n') { line.pop(); }
+                Ok(Some(line))
+            }
+            Format::Fixed(size) => {
+                let mut record = vec![0; size];
+                let mut n = 0;
+                while n < size {
+                    match r.read(&mut record[n..]) {
+                        Ok(0) => break,
+                        Ok(k) => n += k,
+                        Err(e) => {
+                            if e.kind() != io::ErrorKind::Interrupted {
+                                return Err(e);
+                            }
+                        }
+                    }
+                }
+                if n == 0 { return Ok(None); }
+                if n < size {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("input ends with a partial record of {} \
+                                 bytes, not {}", n, size)));
+                }
+                Ok(Some(record))
+            }
+        }
+    }
+
+    fn write<W: Write>(self, w: &mut W, record: &[u8]) -> io::Result<()> {
+        w.write_all(record)?;
+        match self {
+            Format::Lines => w.write_all(b"\n"),
+            Format::Fixed(_) => Ok(()),
+        }
+    }
+}
+
+// The number of bytes of the budget a record takes up.
+fn footprint(record: &[u8]) -> usize {
+    record.len() + mem::size_of::<Vec<u8>>()
+}
+
+fn sort_format<R: Read, W: Write>(input: R, output: W, format: Format,
+                                  options: &Options) -> io::Result<Report> {
+    let mut input = BufReader::new(input);
+    let mut report = Report::default();
+    let mut temp: Option<TempDir> = None;
+    let mut runs = vec![];
+    loop {
+        let mut run = vec![];
+        let mut used = 0;
+        while used < options.memory || run.is_empty() {
+            match format.read(&mut input)? {
+                Some(record) => {
+                    used += footprint(&record);
+                    run.push(record);
+                }
+                None => break,
+            }
+        }
+        if run.is_empty() { break; }
+        report.records += run.len();
+        report.runs += 1;
+        intro::sort(&mut run);
+        if runs.is_empty() && temp.is_none() && used < options.memory {
+            // the whole input fits in memory
+            let mut output = BufWriter::new(output);
+            for record in &run {
+                format.write(&mut output, record)?;
+            }
+            output.flush()?;
+            return Ok(report);
+        }
+        if temp.is_none() { temp = Some(TempDir::new(options)?); }
+        let path = temp.as_mut().unwrap().next_path();
+        let mut file = BufWriter::new(File::create(&path)?);
+        for record in &run {
+            format.write(&mut file, record)?;
+        }
+        file.flush()?;
+        runs.push(path);
+    }
+    if runs.is_empty() { return Ok(report); }
+
+    let fan_in = cmp::min(MAX_FAN_IN, options.memory / MIN_BUFFER);
+    let fan_in = cmp::max(2, fan_in);
+    let buffer = cmp::max(1, options.memory / (fan_in + 1));
+    let temp = temp.as_mut().unwrap();
+    while runs.len() > fan_in {
+        let mut merged = vec![];
+        for group in runs.chunks(fan_in) {
+            let path = temp.next_path();
+            merge(group, File::create(&path)?, format, buffer)?;
+            for run in group { fs::remove_file(run)?; }
+            merged.push(path);
+        }
+        runs = merged;
+        report.passes += 1;
+    }
+    merge(&runs, output, format, buffer)?;
+    report.passes += 1;
+    Ok(report)
+}
+
+// Merge the sorted runs in the files `runs` into `output`, reading each with
+// a buffer of `buffer` bytes.
+fn merge<W: Write>(runs: &[PathBuf], output: W, format: Format,
+                   buffer: usize) -> io::Result<()> {
+    let mut output = BufWriter::with_capacity(buffer, output);
+    let mut readers = vec![];
+    for path in runs {
+        readers.push(BufReader::with_capacity(buffer, File::open(path)?));
+    }
+    let mut pq = IndexMinPQ::new(runs.len());
+    for (i, reader) in readers.iter_mut().enumerate() {
+        if let Some(record) = format.read(reader)? {
+            pq.insert(i, record);
+        }
+    }
+    while let Some((i, record)) = pq.pop_min() {
+        format.write(&mut output, &record)?;
+        if let Some(next) = format.read(&mut readers[i])? {
+            pq.insert(i, next);
+        }
+    }
+    output.flush()
+}
+
+// A directory for the runs of one sort, removed with its contents when it's
+// dropped.
+struct TempDir {
+    path: PathBuf,
+    files: usize,
+}
+
+impl TempDir {
+    fn new(options: &Options) -> io::Result<TempDir> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("external-sort-{}-{}", process::id(),
+                           COUNT.fetch_add(1, Ordering::SeqCst));
+        let path = options.temp_dir.join(name);
+        fs::create_dir(&path)?;
+        Ok(TempDir { path, files: 0 })
+    }
+
+    // Return the path of a new file in the directory.
+    fn next_path(&mut self) -> PathBuf {
+        self.files += 1;
+        self.path.join(format!("run-{}", self.files))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::{self, Cursor, Read};
+    use std::path::PathBuf;
+    use super::{sort_lines, sort_records, Options, Report};
+    use super::super::tests::random_permutation;
+
+    // Options with a memory budget of `memory` bytes and a temporary
+    // directory of their own.
+    fn options(memory: usize, name: &str) -> Options {
+        let temp_dir = env::temp_dir().join(format!("external-test-{}", name));
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir(&temp_dir).unwrap();
+        Options { memory, temp_dir }
+    }
+
+    // Check that the sort left no temporary files behind, and clean up.
+    fn check_clean(temp_dir: &PathBuf) {
+        assert_eq!(fs::read_dir(temp_dir).unwrap().count(), 0);
+        fs::remove_dir(temp_dir).unwrap();
+    }
+
+    fn random_lines(n: usize) -> Vec<String> {
+        random_permutation(n, 1).iter()
+                                .map(|x| format!("line {}", x * 7919 % 1000))
+                                .collect()
+    }
+
+    fn sorted_lines(input: &[String], memory: usize, name: &str)
+        -> (String, Report) {
+        let options = options(memory, name);
+        let mut output = vec![];
+        let report = sort_lines(input.join("\n").as_bytes(), &mut output,
+                                &options).unwrap();
+        check_clean(&options.temp_dir);
+        (String::from_utf8(output).unwrap(), report)
+    }
+
+    #[test]
+    fn sort_lines_in_memory() {
+        let input = random_lines(1000);
+        let (output, report) = sorted_lines(&input, 1 << 20, "in_memory");
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(output, expected.join("\n") + "\n");
+        assert_eq!(report, Report { records: 1000, runs: 1, passes: 0 });
+    }
+
+    #[test]
+    fn sort_lines_with_small_budgets() {
+        let input = random_lines(2000);
+        let mut expected = input.clone();
+        expected.sort();
+        let expected = expected.join("\n") + "\n";
+        for &memory in &[1, 100, 1000, 10000, 20000] {
+            let name = format!("small_{}", memory);
+            let (output, report) = sorted_lines(&input, memory, &name);
+            assert_eq!(output, expected);
+            assert_eq!(report.records, 2000);
+            assert!(report.runs > 1);
+            assert!(report.passes >= 1);
+        }
+        // one record per run, merged two at a time
+        let (_, report) = sorted_lines(&input, 1, "one_per_run");
+        assert_eq!(report.runs, 2000);
+        assert_eq!(report.passes, 11);
+    }
+
+    #[test]
+    fn sort_lines_edge_cases() {
+        let (output, report) = sorted_lines(&[], 10, "empty");
+        assert_eq!(output, "");
+        assert_eq!(report, Report::default());
+
+        let input = vec!["b".to_string(), "".to_string(), "a".to_string(),
+                         "".to_string()];
+        // the last empty string is just the newline at the end of "a"
+        let (output, report) = sorted_lines(&input, 1, "blank");
+        assert_eq!(output, "\na\nb\n");
+        assert_eq!(report.records, 3);
+    }
+
+    #[test]
+    fn sort_records_works() {
+        let input: Vec<u8> = random_permutation(5000, 2).iter()
+            .flat_map(|&x| (x as u32).to_be_bytes().to_vec())
+            .collect();
+        for &memory in &[1, 1000, 1 << 20] {
+            let options = options(memory, &format!("records_{}", memory));
+            let mut output = vec![];
+            let report = sort_records(&input[..], &mut output, 4, &options)
+                             .unwrap();
+            check_clean(&options.temp_dir);
+            assert_eq!(report.records, 5000);
+            let expected: Vec<u8> = (0..5000u32)
+                .flat_map(|x| x.to_be_bytes().to_vec())
+                .collect();
+            assert!(output == expected);
+        }
+    }
+
+    #[test]
+    fn sort_records_rejects_partial_records() {
+        let options = options(16, "partial");
+        let mut output = vec![];
+        let err = sort_records(&[1, 2, 3, 4, 5, 6, 7][..], &mut output, 3,
+                               &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        check_clean(&options.temp_dir);
+    }
+
+    #[test]
+    fn sort_reads_from_any_reader() {
+        let options = options(64, "reader");
+        let input = Cursor::new("c\nb\na\n").chain(Cursor::new("e\nd\n"));
+        let mut output = vec![];
+        sort_lines(input, &mut output, &options).unwrap();
+        check_clean(&options.temp_dir);
+        assert_eq!(output, b"a\nb\nc\nd\ne\n");
+    }
+
+    #[test]
+    #[should_panic]
+    fn sort_records_of_size_0() {
+        let mut output = vec![];
+        let _ = sort_records(&[][..], &mut output, 0, &Options::default());
+    }
+}
@@ -0,0 +1,528 @@
+//! Binomial heaps (Vuillemin, 1978).
+//!
+//! A binomial heap is a list of heap-ordered binomial trees with distinct
+//! orders, like the bits of the binary representation of its size. Melding
+//! two heaps links trees of equal order as adding binary numbers carries
+//! bits, so `meld`, `insert`, `pop`, `increase_key`, `decrease_key` and
+//! `delete` all take O(log N) time.
+//!
+//! The nodes are shared with `PairingHeap`, and linked with raw pointers. All
+//! `unsafe` blocks in this module rely on the same invariant:
+//!
+//! - `head` is null if the heap is empty; otherwise it points to the first of
+//!   the roots, linked through `sibling` in increasing order of `degree`.
+//! - The `child` of a node points to its child of the highest degree, and its
+//!   children are linked through `sibling` in decreasing order of degree, all
+//!   with `up` pointing back at it. Roots have a null `up`.
+//! - Every node reachable from `head` was allocated by `new_node` and is owned
+//!   exclusively by the heap, which holds `n` of them.
+//! - `max` is null if the heap is empty; otherwise it points to a root with
+//!   the largest key.
+//! - The slot of every node points back at the node, and the slot of every
+//!   element that has left the heap is null.
+
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use super::{free, free_trees, into_sorted_vec, new_node, Link, MaxPQLike,
+            Node, Owner, Slot};
+pub use super::{Handle, Iter};
+
+/// A priority queue based on a binomial heap that removes the largest
+/// element first, with the same interface as `MaxPQ` and also fast melding
+/// and changing of keys through handles.
+pub struct BinomialHeap<T: Ord> {
+    head: Link<T>,
+    max: Link<T>,
+    n: usize,
+    owner: Rc<Owner>,
+    // the heap owns its nodes
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    /// Constructs a new, empty heap.
+    pub fn new() -> BinomialHeap<T> {
+        BinomialHeap {
+            head: ptr::null_mut(),
+            max: ptr::null_mut(),
+            n: 0,
+            owner: Owner::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Constructs a heap that holds the elements of `v`.
+    pub fn from_vec(v: Vec<T>) -> BinomialHeap<T> {
+        let mut heap = BinomialHeap::new();
+        heap.extend(v);
+        heap
+    }
+
+    /// Adds an element to the heap and returns a handle to it.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let (node, handle) = new_node(value, &self.owner);
+        self.n += 1;
+        // SAFETY: `node` is a new root that's owned by the heap.
+        unsafe { self.union(node); }
+        handle
+    }
+
+    /// Removes the largest element and returns it, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.max.is_null() { return None; }
+        let node = self.max;
+        self.n -= 1;
+        // SAFETY: `max` is a root of the heap, and once it's removed nothing
+        // else refers to it.
+        unsafe {
+            self.remove_root(node);
+            Some(free(node))
+        }
+    }
+
+    /// Removes the largest element and returns it, or `None` if the heap is
+    /// empty. The same as `pop`.
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    /// Returns a reference to the largest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: `max` is null or points to a node owned by the heap.
+        unsafe { self.max.as_ref() }.map(|node| &node.key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Returns an iterator over the elements of the heap, in no particular
+    /// order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.head, self.n)
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        into_sorted_vec(self)
+    }
+
+    /// Moves all the elements of `other` into this heap, in O(log N) time.
+    /// Handles to the elements of `other` refer to them in this heap.
+    pub fn meld(&mut self, mut other: BinomialHeap<T>) {
+        Owner::join(&other.owner, &self.owner);
+        let head = mem::replace(&mut other.head, ptr::null_mut());
+        other.max = ptr::null_mut();
+        self.n += mem::replace(&mut other.n, 0);
+        // SAFETY: the roots of `other` are now owned by this heap.
+        unsafe { self.union(head); }
+    }
+
+    /// Returns `true` if the element that `handle` refers to is in the heap.
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        handle.node_in(&self.owner).is_some()
+    }
+
+    /// Returns a reference to the element that `handle` refers to, or `None`
+    /// if it isn't in the heap.
+    pub fn key_of(&self, handle: &Handle<T>) -> Option<&T> {
+        // SAFETY: the slot points to a node owned by the heap.
+        handle.node_in(&self.owner).map(|node| unsafe { &(*node).key })
+    }
+
+    /// Increases the element that `handle` refers to to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap, or if `key` isn't greater
+    /// than it.
+    pub fn increase_key(&mut self, handle: &Handle<T>, key: T) {
+        let node = handle.node_of(&self.owner);
+        // SAFETY: `node` is owned by the heap, and `max` isn't null since
+        // the heap isn't empty.
+        unsafe {
+            assert!(key > (*node).key,
+                    "increase_key: key isn't greater than the current key");
+            (*node).key = key;
+            let node = self.sift_up(node, false);
+            if (*node).up.is_null() && (*node).key > (*self.max).key {
+                self.max = node;
+            }
+        }
+    }
+
+    /// Decreases the element that `handle` refers to to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap, or if `key` isn't less than
+    /// it.
+    pub fn decrease_key(&mut self, handle: &Handle<T>, key: T) {
+        let node = handle.node_of(&self.owner);
+        // SAFETY: `node` is owned by the heap, and after moving it to the
+        // root of its tree and removing that, it's a node on its own.
+        unsafe {
+            assert!(key < (*node).key,
+                    "decrease_key: key isn't less than the current key");
+            let node = self.sift_up(node, true);
+            self.remove_root(node);
+            (*node).key = key;
+            (*node).degree = 0;
+            self.union(node);
+        }
+    }
+
+    /// Removes the element that `handle` refers to and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap.
+    pub fn delete(&mut self, handle: &Handle<T>) -> T {
+        let node = handle.node_of(&self.owner);
+        self.n -= 1;
+        // SAFETY: `node` is owned by the heap, and once it's removed nothing
+        // else refers to it.
+        unsafe {
+            let node = self.sift_up(node, true);
+            self.remove_root(node);
+            free(node)
+        }
+    }
+
+    // Move the key of `node` up its tree while it's greater than its
+    // parent's, or all the way to the root if `to_root` is true. Returns the
+    // node that holds the key.
+    unsafe fn sift_up(&mut self, node: Link<T>, to_root: bool) -> Link<T> {
+        let mut x = node;
+        while ! (*x).up.is_null() {
+            let parent = (*x).up;
+            if ! to_root && (*x).key <= (*parent).key { break; }
+            mem::swap(&mut (*x).key, &mut (*parent).key);
+            mem::swap(&mut (*x).slot, &mut (*parent).slot);
+            for &node in &[x, parent] {
+                let slot: &Slot<_> = &(*node).slot;
+                slot.node.set(node);
+            }
+            x = parent;
+        }
+        x
+    }
+
+    // Remove the root `node` from the list of roots and meld its children
+    // back into the heap, leaving `node` on its own.
+    unsafe fn remove_root(&mut self, node: Link<T>) {
+        if self.head == node {
+            self.head = (*node).sibling;
+        } else {
+            let mut prev = self.head;
+            while (*prev).sibling != node {
+                prev = (*prev).sibling;
+            }
+            (*prev).sibling = (*node).sibling;
+        }
+        // the children are in decreasing order of degree, so reverse them to
+        // make a list of roots
+        let mut children = ptr::null_mut();
+        let mut x = (*node).child;
+        while ! x.is_null() {
+            let next = (*x).sibling;
+            (*x).up = ptr::null_mut();
+            (*x).sibling = children;
+            children = x;
+            x = next;
+        }
+        (*node).child = ptr::null_mut();
+        (*node).sibling = ptr::null_mut();
+        self.union(children);
+    }
+
+    // Meld the list of roots that starts at `other` into the heap, linking
+    // roots of equal degree, and find the new largest root.
+    unsafe fn union(&mut self, other: Link<T>) {
+        let mut head = merge_roots(self.head, other);
+        let mut prev: Link<T> = ptr::null_mut();
+        let mut x = head;
+        while ! x.is_null() && ! (*x).sibling.is_null() {
+            let next = (*x).sibling;
+            let after = (*next).sibling;
+            if (*x).degree != (*next).degree ||
+               (! after.is_null() && (*after).degree == (*x).degree) {
+                prev = x;
+                x = next;
+            } else if (*x).key >= (*next).key {
+                (*x).sibling = after;
+                link(next, x);
+            } else {
+                if prev.is_null() { head = next; }
+                else              { (*prev).sibling = next; }
+                link(x, next);
+                x = next;
+            }
+        }
+        self.head = head;
+        self.max = head;
+        let mut x = head;
+        while ! x.is_null() {
+            if (*x).key > (*self.max).key { self.max = x; }
+            x = (*x).sibling;
+        }
+    }
+}
+
+// Merge two lists of roots in increasing order of degree into one.
+unsafe fn merge_roots<T>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (mut a, mut b) = (a, b);
+    let mut head = ptr::null_mut();
+    let mut tail: *mut Link<T> = &mut head;
+    while ! a.is_null() && ! b.is_null() {
+        let next;
+        if (*a).degree <= (*b).degree {
+            next = a;
+            a = (*a).sibling;
+        } else {
+            next = b;
+            b = (*b).sibling;
+        }
+        *tail = next;
+        tail = &mut (*next).sibling;
+    }
+    *tail = if a.is_null() { b } else { a };
+    head
+}
+
+// Make the root `child` the first child of the root `parent`, which has the
+// same degree.
+unsafe fn link<T>(child: Link<T>, parent: Link<T>) {
+    (*child).up = parent;
+    (*child).sibling = (*parent).child;
+    (*parent).child = child;
+    (*parent).degree += 1;
+}
+
+impl<T: Ord> Drop for BinomialHeap<T> {
+    fn drop(&mut self) {
+        // SAFETY: the heap owns the nodes reachable from `head`.
+        unsafe { free_trees(self.head); }
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> BinomialHeap<T> {
+        BinomialHeap::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinomialHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BinomialHeap<T> {
+        BinomialHeap::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> Extend<T> for BinomialHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> MaxPQLike<T> for BinomialHeap<T> {
+    fn insert(&mut self, value: T) {
+        BinomialHeap::insert(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        BinomialHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        BinomialHeap::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        BinomialHeap::is_empty(self)
+    }
+
+    fn size(&self) -> usize {
+        BinomialHeap::size(self)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a BinomialHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+    use super::{BinomialHeap, Handle, Link};
+    use super::super::Slot;
+    use super::super::tests::{test_works_for_1_element, test_pops_in_order,
+                              test_meld_works,
+                              test_handles_change_and_delete_keys,
+                              test_increase_key_needs_a_greater_key,
+                              test_decrease_key_needs_a_smaller_key,
+                              test_handles_of_other_heaps_panic,
+                              test_handles_of_popped_elements_panic,
+                              test_handles_outlive_the_heap,
+                              test_handles_follow_melds, Mergeable};
+
+    impl Mergeable for BinomialHeap<isize> {
+        fn insert(&mut self, value: isize) -> Handle<isize> {
+            BinomialHeap::insert(self, value)
+        }
+
+        fn pop(&mut self) -> Option<isize> {
+            BinomialHeap::pop(self)
+        }
+
+        fn peek(&self) -> Option<&isize> {
+            BinomialHeap::peek(self)
+        }
+
+        fn size(&self) -> usize {
+            BinomialHeap::size(self)
+        }
+
+        fn elements(&self) -> Vec<isize> {
+            self.into_iter().cloned().collect()
+        }
+
+        fn into_sorted_vec(self) -> Vec<isize> {
+            BinomialHeap::into_sorted_vec(self)
+        }
+
+        fn meld(&mut self, other: BinomialHeap<isize>) {
+            BinomialHeap::meld(self, other)
+        }
+
+        fn contains(&self, handle: &Handle<isize>) -> bool {
+            BinomialHeap::contains(self, handle)
+        }
+
+        fn key_of(&self, handle: &Handle<isize>) -> Option<&isize> {
+            BinomialHeap::key_of(self, handle)
+        }
+
+        fn increase_key(&mut self, handle: &Handle<isize>, key: isize) {
+            BinomialHeap::increase_key(self, handle, key)
+        }
+
+        fn decrease_key(&mut self, handle: &Handle<isize>, key: isize) {
+            BinomialHeap::decrease_key(self, handle, key)
+        }
+
+        fn delete(&mut self, handle: &Handle<isize>) -> isize {
+            BinomialHeap::delete(self, handle)
+        }
+
+        // Check the invariants of the heap.
+        fn check(&self) {
+            unsafe fn check_tree(node: Link<isize>, up: Link<isize>)
+                                 -> usize {
+                assert!((*node).up == up);
+                let slot: &Slot<_> = &(*node).slot;
+                assert!(slot.node.get() == node);
+                let mut size = 1;
+                let mut degree = (*node).degree;
+                let mut child = (*node).child;
+                while ! child.is_null() {
+                    degree -= 1;
+                    assert_eq!((*child).degree, degree);
+                    assert!((*child).key <= (*node).key);
+                    size += check_tree(child, node);
+                    child = (*child).sibling;
+                }
+                assert_eq!(degree, 0);
+                assert_eq!(size, 1 << (*node).degree);
+                size
+            }
+
+            let mut n = 0;
+            let mut x = self.head;
+            let mut max: Link<isize> = ptr::null_mut();
+            unsafe {
+                while ! x.is_null() {
+                    if ! (*x).sibling.is_null() {
+                        assert!((*x).degree < (*(*x).sibling).degree);
+                    }
+                    n += check_tree(x, ptr::null_mut());
+                    if max.is_null() || (*x).key > (*max).key { max = x; }
+                    x = (*x).sibling;
+                }
+                assert_eq!(n, self.size());
+                if ! max.is_null() { assert!((*max).key == (*self.max).key); }
+            }
+            assert!(max.is_null() == self.max.is_null());
+        }
+    }
+
+    #[test]
+    fn works_for_1_element() {
+        test_works_for_1_element::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    fn pops_in_order() {
+        test_pops_in_order::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    fn meld_works() {
+        test_meld_works::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_change_and_delete_keys() {
+        test_handles_change_and_delete_keys::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't greater")]
+    fn increase_key_needs_a_greater_key() {
+        test_increase_key_needs_a_greater_key::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't less")]
+    fn decrease_key_needs_a_smaller_key() {
+        test_decrease_key_needs_a_smaller_key::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't in the heap")]
+    fn handles_of_other_heaps_panic() {
+        test_handles_of_other_heaps_panic::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't in the heap")]
+    fn handles_of_popped_elements_panic() {
+        test_handles_of_popped_elements_panic::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_outlive_the_heap() {
+        test_handles_outlive_the_heap::<BinomialHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_follow_melds() {
+        test_handles_follow_melds::<BinomialHeap<isize>>();
+    }
+}
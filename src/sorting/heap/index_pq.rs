@@ -0,0 +1,568 @@
+//! Indexed priority queues (page 320).
+//!
+//! An indexed priority queue associates each key with an integer index in
+//! `0..capacity`, so that clients like Dijkstra's and Prim's algorithms can
+//! refer to the keys in the queue and change them.
+
+use std::cmp::Ordering;
+
+use super::{reverse_cmp, sink, swim, Compare, Heap, DEFAULT_ARITY};
+
+/// An indexed priority queue based on a binary heap, with the order defined
+/// by a comparison function: the top of the queue is the greatest key
+/// according to `compare`.
+pub struct IndexPQ<K, F> {
+    // binary heap of indices
+    pq: Vec<usize>,
+    // `qp[i]` is the position of index `i` in `pq`, if it's in the queue
+    qp: Vec<Option<usize>>,
+    keys: Vec<Option<K>>,
+    compare: F,
+}
+
+// The heap of indices in an `IndexPQ`, which keeps `qp` up to date as
+// elements move.
+struct IndexHeap<'a, K: 'a, F: 'a> {
+    pq: &'a mut [usize],
+    qp: &'a mut [Option<usize>],
+    keys: &'a [Option<K>],
+    compare: &'a mut F,
+}
+
+impl<'a, K, F> Heap for IndexHeap<'a, K, F>
+    where F: FnMut(&K, &K) -> Ordering {
+    fn less(&mut self, i: usize, j: usize) -> bool {
+        let a = self.keys[self.pq[i]].as_ref().unwrap();
+        let b = self.keys[self.pq[j]].as_ref().unwrap();
+        (self.compare)(a, b) == Ordering::Less
+    }
+
+    fn exch(&mut self, i: usize, j: usize) {
+        self.pq.swap(i, j);
+        self.qp[self.pq[i]] = Some(i);
+        self.qp[self.pq[j]] = Some(j);
+    }
+}
+
+impl<K, F> IndexPQ<K, F> where F: FnMut(&K, &K) -> Ordering {
+    /// Constructs a new, empty queue for indices in `0..capacity`, ordered by
+    /// `compare`.
+    pub fn with_comparator(capacity: usize, compare: F) -> IndexPQ<K, F> {
+        IndexPQ {
+            pq: Vec::with_capacity(capacity),
+            qp: vec![None; capacity],
+            keys: (0..capacity).map(|_| None).collect(),
+            compare,
+        }
+    }
+
+    fn heap(&mut self) -> IndexHeap<'_, K, F> {
+        IndexHeap {
+            pq: &mut self.pq,
+            qp: &mut self.qp,
+            keys: &self.keys,
+            compare: &mut self.compare,
+        }
+    }
+
+    // Panic unless `i` is in `0..capacity`.
+    fn check_index(&self, i: usize) {
+        assert!(i < self.qp.len(), "index {} out of range", i);
+    }
+
+    // Panic unless `i` is a valid index that's in the queue.
+    fn position(&self, i: usize) -> usize {
+        self.check_index(i);
+        self.qp[i].unwrap_or_else(|| panic!("index {} is not in the queue", i))
+    }
+
+    /// Returns `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pq.is_empty()
+    }
+
+    /// Returns the number of keys in the queue.
+    pub fn size(&self) -> usize {
+        self.pq.len()
+    }
+
+    /// Returns `true` if index `i` is in the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn contains(&self, i: usize) -> bool {
+        self.check_index(i);
+        self.qp[i].is_some()
+    }
+
+    /// Associates `key` with index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or already in the queue.
+    pub fn insert(&mut self, i: usize, key: K) {
+        assert!( ! self.contains(i), "index {} is already in the queue", i);
+        let n = self.pq.len();
+        self.pq.push(i);
+        self.qp[i] = Some(n);
+        self.keys[i] = Some(key);
+        swim(&mut self.heap(), n, DEFAULT_ARITY);
+    }
+
+    /// Returns the index associated with the top key, or `None` if the queue
+    /// is empty.
+    pub fn top_index(&self) -> Option<usize> {
+        self.pq.first().cloned()
+    }
+
+    /// Returns the top key, or `None` if the queue is empty.
+    pub fn top_key(&self) -> Option<&K> {
+        self.pq.first().and_then(|&i| self.keys[i].as_ref())
+    }
+
+    /// Returns the key associated with index `i`, or `None` if `i` isn't in
+    /// the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn key_of(&self, i: usize) -> Option<&K> {
+        self.check_index(i);
+        self.keys[i].as_ref()
+    }
+
+    /// Removes the top key and returns it with its index, or returns `None`
+    /// if the queue is empty.
+    pub fn pop(&mut self) -> Option<(usize, K)> {
+        let i = self.top_index()?;
+        Some((i, self.delete(i)))
+    }
+
+    /// Changes the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn change_key(&mut self, i: usize, key: K) {
+        let p = self.position(i);
+        self.keys[i] = Some(key);
+        let n = self.pq.len();
+        swim(&mut self.heap(), p, DEFAULT_ARITY);
+        let p = self.position(i);
+        sink(&mut self.heap(), p, n, DEFAULT_ARITY);
+    }
+
+    /// Removes index `i` and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn delete(&mut self, i: usize) -> K {
+        let p = self.position(i);
+        let n = self.pq.len() - 1;
+        self.heap().exch(p, n);
+        self.pq.pop();
+        self.qp[i] = None;
+        let key = self.keys[i].take().unwrap();
+        if p < n {
+            swim(&mut self.heap(), p, DEFAULT_ARITY);
+            sink(&mut self.heap(), p, n, DEFAULT_ARITY);
+        }
+        key
+    }
+
+    // Compare `key` with the key of index `i`.
+    fn compare_with(&mut self, key: &K, i: usize) -> Ordering {
+        self.position(i);
+        (self.compare)(key, self.keys[i].as_ref().unwrap())
+    }
+}
+
+/// An indexed priority queue whose top is the smallest key.
+pub struct IndexMinPQ<K: Ord>(IndexPQ<K, Compare<K>>);
+
+impl<K: Ord> IndexMinPQ<K> {
+    /// Constructs a new, empty queue for indices in `0..capacity`.
+    pub fn new(capacity: usize) -> IndexMinPQ<K> {
+        IndexMinPQ(IndexPQ::with_comparator(capacity, reverse_cmp))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    /// Returns `true` if index `i` is in the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn contains(&self, i: usize) -> bool {
+        self.0.contains(i)
+    }
+
+    /// Associates `key` with index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or already in the queue.
+    pub fn insert(&mut self, i: usize, key: K) {
+        self.0.insert(i, key);
+    }
+
+    /// Returns the index associated with the smallest key.
+    pub fn min_index(&self) -> Option<usize> {
+        self.0.top_index()
+    }
+
+    /// Returns the smallest key.
+    pub fn min_key(&self) -> Option<&K> {
+        self.0.top_key()
+    }
+
+    /// Returns the key associated with index `i`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn key_of(&self, i: usize) -> Option<&K> {
+        self.0.key_of(i)
+    }
+
+    /// Removes the smallest key and returns it with its index.
+    pub fn pop_min(&mut self) -> Option<(usize, K)> {
+        self.0.pop()
+    }
+
+    /// Changes the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn change_key(&mut self, i: usize, key: K) {
+        self.0.change_key(i, key);
+    }
+
+    /// Decreases the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue, or if `key` isn't
+    /// less than the current key.
+    pub fn decrease_key(&mut self, i: usize, key: K) {
+        assert!(self.0.compare_with(&key, i) == Ordering::Greater,
+                "decrease_key: key isn't less than the current key");
+        self.0.change_key(i, key);
+    }
+
+    /// Increases the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue, or if `key` isn't
+    /// greater than the current key.
+    pub fn increase_key(&mut self, i: usize, key: K) {
+        assert!(self.0.compare_with(&key, i) == Ordering::Less,
+                "increase_key: key isn't greater than the current key");
+        self.0.change_key(i, key);
+    }
+
+    /// Removes index `i` and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn delete(&mut self, i: usize) -> K {
+        self.0.delete(i)
+    }
+}
+
+/// An indexed priority queue whose top is the largest key.
+pub struct IndexMaxPQ<K: Ord>(IndexPQ<K, Compare<K>>);
+
+impl<K: Ord> IndexMaxPQ<K> {
+    /// Constructs a new, empty queue for indices in `0..capacity`.
+    pub fn new(capacity: usize) -> IndexMaxPQ<K> {
+        IndexMaxPQ(IndexPQ::with_comparator(capacity, K::cmp))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    /// Returns `true` if index `i` is in the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn contains(&self, i: usize) -> bool {
+        self.0.contains(i)
+    }
+
+    /// Associates `key` with index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or already in the queue.
+    pub fn insert(&mut self, i: usize, key: K) {
+        self.0.insert(i, key);
+    }
+
+    /// Returns the index associated with the largest key.
+    pub fn max_index(&self) -> Option<usize> {
+        self.0.top_index()
+    }
+
+    /// Returns the largest key.
+    pub fn max_key(&self) -> Option<&K> {
+        self.0.top_key()
+    }
+
+    /// Returns the key associated with index `i`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    pub fn key_of(&self, i: usize) -> Option<&K> {
+        self.0.key_of(i)
+    }
+
+    /// Removes the largest key and returns it with its index.
+    pub fn pop_max(&mut self) -> Option<(usize, K)> {
+        self.0.pop()
+    }
+
+    /// Changes the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn change_key(&mut self, i: usize, key: K) {
+        self.0.change_key(i, key);
+    }
+
+    /// Decreases the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue, or if `key` isn't
+    /// less than the current key.
+    pub fn decrease_key(&mut self, i: usize, key: K) {
+        assert!(self.0.compare_with(&key, i) == Ordering::Less,
+                "decrease_key: key isn't less than the current key");
+        self.0.change_key(i, key);
+    }
+
+    /// Increases the key associated with index `i` to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue, or if `key` isn't
+    /// greater than the current key.
+    pub fn increase_key(&mut self, i: usize, key: K) {
+        assert!(self.0.compare_with(&key, i) == Ordering::Greater,
+                "increase_key: key isn't greater than the current key");
+        self.0.change_key(i, key);
+    }
+
+    /// Removes index `i` and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or not in the queue.
+    pub fn delete(&mut self, i: usize) -> K {
+        self.0.delete(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use rand::{Rng, SeedableRng, StdRng};
+    use super::{IndexMaxPQ, IndexMinPQ, IndexPQ};
+    use super::super::DEFAULT_ARITY;
+
+    // Check that the heap is ordered and that `pq` and `qp` are inverses.
+    fn check<K, F>(q: &IndexPQ<K, F>) where F: Fn(&K, &K) -> Ordering {
+        for (p, &i) in q.pq.iter().enumerate() {
+            assert_eq!(q.qp[i], Some(p));
+            assert!(q.keys[i].is_some());
+            if p > 0 {
+                let parent = q.keys[q.pq[(p - 1) / DEFAULT_ARITY]].as_ref();
+                let order = (q.compare)(parent.unwrap(),
+                                        q.keys[i].as_ref().unwrap());
+                assert!(order != Ordering::Less,
+                        "heap order violated at {}", p);
+            }
+        }
+        let n_present = q.qp.iter().filter(|p| p.is_some()).count();
+        assert_eq!(n_present, q.pq.len());
+    }
+
+    #[test]
+    fn index_min_pq_works() {
+        let words = ["it", "was", "the", "best", "of", "times", "it", "was"];
+        let mut pq = IndexMinPQ::new(words.len());
+        assert!(pq.is_empty());
+        assert_eq!(pq.min_index(), None);
+        for (i, w) in words.iter().enumerate() {
+            pq.insert(i, w);
+        }
+        assert_eq!(pq.size(), 8);
+        assert_eq!(pq.min_index(), Some(3));
+        assert_eq!(pq.min_key(), Some(&&"best"));
+        assert!(pq.contains(5));
+        assert_eq!(pq.key_of(5), Some(&&"times"));
+
+        let mut output = vec![];
+        while let Some((i, w)) = pq.pop_min() {
+            check(&pq.0);
+            assert!( ! pq.contains(i));
+            output.push(*w);
+        }
+        let expected = ["best", "it", "it", "of", "the", "times", "was", "was"];
+        assert_eq!(output, expected);
+        assert_eq!(pq.key_of(5), None);
+    }
+
+    #[test]
+    fn change_keys() {
+        let mut pq = IndexMinPQ::new(10);
+        for i in 0..10 {
+            pq.insert(i, 10 * i as isize);
+        }
+        pq.decrease_key(7, -1);
+        assert_eq!(pq.min_index(), Some(7));
+        pq.increase_key(7, 100);
+        assert_eq!(pq.min_index(), Some(0));
+        pq.change_key(0, 55);
+        assert_eq!(pq.min_index(), Some(1));
+        pq.change_key(9, 5);
+        assert_eq!(pq.min_index(), Some(9));
+        assert_eq!(pq.delete(9), 5);
+        assert!( ! pq.contains(9));
+        check(&pq.0);
+
+        let order: Vec<usize> =
+            (0..9).map(|_| pq.pop_min().unwrap().0).collect();
+        assert_eq!(order, vec![1, 2, 3, 4, 5, 0, 6, 8, 7]);
+    }
+
+    #[test]
+    fn index_max_pq_works() {
+        let mut pq = IndexMaxPQ::new(5);
+        for (i, &k) in [3, 1, 4, 1, 5].iter().enumerate() {
+            pq.insert(i, k);
+        }
+        check(&pq.0);
+        assert_eq!(pq.max_index(), Some(4));
+        pq.decrease_key(4, 0);
+        assert_eq!(pq.max_index(), Some(2));
+        pq.increase_key(1, 9);
+        assert_eq!(pq.max_key(), Some(&9));
+        assert_eq!(pq.pop_max(), Some((1, 9)));
+        assert_eq!(pq.pop_max(), Some((2, 4)));
+        assert_eq!(pq.delete(0), 3);
+        assert_eq!(pq.pop_max(), Some((3, 1)));
+        assert_eq!(pq.pop_max(), Some((4, 0)));
+        assert_eq!(pq.pop_max(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "already in the queue")]
+    fn insert_twice_panics() {
+        let mut pq = IndexMinPQ::new(3);
+        pq.insert(1, 'a');
+        pq.insert(1, 'b');
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't less")]
+    fn decrease_key_to_larger_key_panics() {
+        let mut pq = IndexMinPQ::new(3);
+        pq.insert(1, 5);
+        pq.decrease_key(1, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in the queue")]
+    fn change_key_of_missing_index_panics() {
+        let mut pq = IndexMinPQ::new(3);
+        pq.insert(1, 5);
+        pq.change_key(2, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_out_of_range_panics() {
+        let mut pq = IndexMinPQ::new(3);
+        pq.insert(3, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn contains_out_of_range_panics() {
+        let pq: IndexMinPQ<isize> = IndexMinPQ::new(3);
+        pq.contains(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn key_of_out_of_range_panics() {
+        let pq: IndexMaxPQ<isize> = IndexMaxPQ::new(3);
+        pq.key_of(3);
+    }
+
+    #[test]
+    fn random_operations_match_brute_force() {
+        let n = 50;
+        let seed: &[_] = &[1];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut pq = IndexMinPQ::new(n);
+        let mut keys: Vec<Option<isize>> = vec![None; n];
+        for _ in 0..5000 {
+            let i = rng.gen_range(0, n);
+            let key = rng.gen_range(0, 1000);
+            match (keys[i], rng.gen_range(0, 4)) {
+                (None, _) => {
+                    pq.insert(i, key);
+                    keys[i] = Some(key);
+                }
+                (Some(_), 0) => {
+                    assert_eq!(Some(pq.delete(i)), keys[i]);
+                    keys[i] = None;
+                }
+                (Some(_), 1) => {
+                    let (j, k) = pq.pop_min().unwrap();
+                    assert_eq!(keys[j], Some(k));
+                    assert_eq!(keys.iter().filter_map(|&k| k).min(), Some(k));
+                    keys[j] = None;
+                }
+                (Some(_), _) => {
+                    pq.change_key(i, key);
+                    keys[i] = Some(key);
+                }
+            }
+            check(&pq.0);
+
+            for (j, &k) in keys.iter().enumerate() {
+                assert_eq!(pq.key_of(j).cloned(), k);
+                assert_eq!(pq.contains(j), k.is_some());
+            }
+            assert_eq!(pq.size(), keys.iter().filter(|k| k.is_some()).count());
+            let min = keys.iter().filter_map(|&k| k).min();
+            assert_eq!(pq.min_key().cloned(), min);
+            if let Some(i) = pq.min_index() {
+                assert_eq!(keys[i], min);
+            }
+        }
+    }
+}
@@ -1,33 +1,519 @@
 //! Algorithms based on heaps
 
+pub mod binomial;
+pub mod index_pq;
+pub mod multiway;
+pub mod pairing;
 pub mod pq;
 pub mod sort;
+pub mod top_k;
 
-fn swim<T: Ord>(v: &mut Vec<T>, i: usize) {
+use std::cell::{Cell, RefCell};
+use std::cmp::{self, Ordering};
+use std::marker::PhantomData;
+use std::ptr;
+use std::rc::Rc;
+
+use super::Ops;
+use super::stats::Recorder;
+
+/// Operations shared by the priority queues that remove the largest element
+/// first, so that clients can be generic over the representation.
+pub trait MaxPQLike<T> {
+    /// Adds an element to the queue.
+    fn insert(&mut self, value: T);
+
+    /// Removes the largest element and returns it.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns a reference to the largest element.
+    fn peek(&self) -> Option<&T>;
+
+    /// Returns `true` if the queue is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns the number of elements in the queue.
+    fn size(&self) -> usize;
+}
+
+// Pop the elements of `pq` into a vector in ascending order.
+fn into_sorted_vec<T, Q: MaxPQLike<T>>(mut pq: Q) -> Vec<T> {
+    let mut v = Vec::with_capacity(pq.size());
+    while let Some(value) = pq.pop() {
+        v.push(value);
+    }
+    v.reverse();
+    v
+}
+
+// The operations that `swim` and `sink` need on a binary heap stored in an
+// array with its root at index 0. The root holds the largest element.
+trait Heap {
+    // True if the element at position `i` is less than the one at `j`.
+    fn less(&mut self, i: usize, j: usize) -> bool;
+
+    // Exchange the elements at positions `i` and `j`.
+    fn exch(&mut self, i: usize, j: usize);
+}
+
+// The number of children of each node in a heap unless specified otherwise.
+const DEFAULT_ARITY: usize = 2;
+
+// Restore heap order in a `d`-ary heap by moving the element at position `i`
+// up. The parent of the element at position `i` is at `(i - 1) / d`.
+fn swim<H: Heap>(h: &mut H, i: usize, d: usize) {
     let mut i = i;
     while i > 0 {
-        let parent = (i - 1) / 2;
-        if v[i] < v[parent] { break; }
-        v.swap(i, parent);
+        let parent = (i - 1) / d;
+        if h.less(i, parent) { break; }
+        h.exch(i, parent);
         i = parent;
     }
 }
 
-fn sink<T: Ord>(v: &mut Vec<T>, i: usize, n: usize) {
+// Restore heap order in positions `..n` of a `d`-ary heap by moving the
+// element at position `i` down. The children of the element at position `i`
+// are at `d*i + 1` through `d*i + d`.
+fn sink<H: Heap>(h: &mut H, i: usize, n: usize, d: usize) {
     let mut i = i;
     loop {
-        let mut child = 2*i + 1;
-        if child >= n { return; }
-        if child+1 < n && v[child+1] > v[child] { child += 1; }
-        if v[i] > v[child] { return; }
-        v.swap(i, child);
+        let first = d*i + 1;
+        if first >= n { return; }
+        let mut child = first;
+        for c in first+1..cmp::min(first + d, n) {
+            if h.less(child, c) { child = c; }
+        }
+        if h.less(child, i) { return; }
+        h.exch(i, child);
         i = child;
     }
 }
 
+// Panic unless `d` is a valid arity for a heap.
+fn check_arity(d: usize) {
+    assert!(d >= 2, "a heap needs an arity of at least 2, not {}", d);
+}
+
+// A heap stored directly in a slice, with the order defined by `ops`.
+struct SliceHeap<'a, 'b: 'a, T: 'a, F: 'a, R: 'b> {
+    v: &'a mut [T],
+    ops: &'a mut Ops<'b, F, R>,
+}
+
+impl<'a, 'b, T, F, R> Heap for SliceHeap<'a, 'b, T, F, R>
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    fn less(&mut self, i: usize, j: usize) -> bool {
+        self.ops.less(&self.v[i], &self.v[j])
+    }
+
+    fn exch(&mut self, i: usize, j: usize) {
+        self.ops.exch(self.v, i, j);
+    }
+}
+
+// Restore heap order in the `d`-ary heap `v` by moving `v[i]` up, with the
+// order defined by `ops`.
+fn swim_ops<T, F, R>(v: &mut [T], i: usize, d: usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    swim(&mut SliceHeap { v, ops }, i, d);
+}
+
+// Restore heap order in the `d`-ary heap `v[..n]` by moving `v[i]` down, with
+// the order defined by `ops`.
+fn sink_ops<T, F, R>(v: &mut [T], i: usize, n: usize, d: usize,
+                     ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    sink(&mut SliceHeap { v, ops }, i, n, d);
+}
+
+// The comparison function of a priority queue on `Ord` elements.
+type Compare<T> = fn(&T, &T) -> Ordering;
+
+// The comparison function that puts the smallest element at the root.
+fn reverse_cmp<T: Ord>(a: &T, b: &T) -> Ordering {
+    b.cmp(a)
+}
+
+// Identifies the mergeable heap that holds an element. Melding a heap into
+// another points the owner of the first at the owner of the second, so as in
+// union-find, the heap that holds an element is the root of its owner.
+struct Owner {
+    parent: RefCell<Option<Rc<Owner>>>,
+}
+
+impl Owner {
+    fn new() -> Rc<Owner> {
+        Rc::new(Owner { parent: RefCell::new(None) })
+    }
+
+    // Make `owner`, the owner of a heap that's being melded into the heap
+    // owned by `root`, point at `root`.
+    fn join(owner: &Rc<Owner>, root: &Rc<Owner>) {
+        *owner.parent.borrow_mut() = Some(root.clone());
+    }
+
+    // Return the root of `owner`, compressing the path to it.
+    fn find(owner: &Rc<Owner>) -> Rc<Owner> {
+        let mut root = owner.clone();
+        loop {
+            let parent = match *root.parent.borrow() {
+                Some(ref parent) => parent.clone(),
+                None => break,
+            };
+            root = parent;
+        }
+        let mut o = owner.clone();
+        while ! Rc::ptr_eq(&o, &root) {
+            let parent = o.parent.replace(Some(root.clone()));
+            o = parent.expect("path to the root is broken");
+        }
+        root
+    }
+}
+
+// A node of a mergeable heap. The nodes are allocated with `Box::into_raw`
+// and linked with raw pointers, since a node is referred to by its parent,
+// its siblings and its handles; `binomial` and `pairing` each document how
+// they link them. `up` points to the parent of a node in a binomial heap, and
+// to the previous sibling, or the parent of a first child, in a pairing heap.
+// Only binomial heaps use `degree`.
+struct Node<T> {
+    key: T,
+    degree: usize,
+    up: Link<T>,
+    child: Link<T>,
+    sibling: Link<T>,
+    slot: Rc<Slot<T>>,
+}
+
+type Link<T> = *mut Node<T>;
+
+// Where the element that a handle refers to is in a mergeable heap: `node`
+// points to the node that holds the element, or is null once the element has
+// left the heap.
+struct Slot<T> {
+    node: Cell<Link<T>>,
+    owner: Rc<Owner>,
+}
+
+/// A handle to an element of a `BinomialHeap` or a `PairingHeap`, returned by
+/// `insert`, that stays valid while the element is in the heap or in one it's
+/// melded into.
+pub struct Handle<T>(Rc<Slot<T>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        Handle(self.0.clone())
+    }
+}
+
+impl<T> Handle<T> {
+    // Return the node of the element if it's in the heap owned by `owner`.
+    fn node_in(&self, owner: &Rc<Owner>) -> Option<Link<T>> {
+        let node = self.0.node.get();
+        if node.is_null() || ! Rc::ptr_eq(&Owner::find(&self.0.owner), owner) {
+            None
+        } else {
+            Some(node)
+        }
+    }
+
+    // Return the node of the element, which must be in the heap owned by
+    // `owner`.
+    fn node_of(&self, owner: &Rc<Owner>) -> Link<T> {
+        self.node_in(owner)
+            .unwrap_or_else(|| panic!("the element isn't in the heap"))
+    }
+}
+
+// Allocate a node on its own that holds `key`, for the heap owned by `owner`,
+// and return it with a handle to it.
+fn new_node<T>(key: T, owner: &Rc<Owner>) -> (Link<T>, Handle<T>) {
+    let slot = Rc::new(Slot {
+        node: Cell::new(ptr::null_mut()),
+        owner: owner.clone(),
+    });
+    let node = Box::into_raw(Box::new(Node {
+        key,
+        degree: 0,
+        up: ptr::null_mut(),
+        child: ptr::null_mut(),
+        sibling: ptr::null_mut(),
+        slot: slot.clone(),
+    }));
+    slot.node.set(node);
+    (node, Handle(slot))
+}
+
+// Free `node`, which has left its heap, and return its key.
+//
+// SAFETY: `node` must have been allocated by `new_node`, and nothing may
+// refer to it but its slot.
+unsafe fn free<T>(node: Link<T>) -> T {
+    let node = *Box::from_raw(node);
+    node.slot.node.set(ptr::null_mut());
+    node.key
+}
+
+// Free the trees whose roots are linked through `sibling` from `first`,
+// without recursing, so that deep trees can't overflow the call stack.
+//
+// SAFETY: the trees must be owned by a heap that's being dropped.
+unsafe fn free_trees<T>(first: Link<T>) {
+    let mut stack = vec![];
+    if ! first.is_null() { stack.push(first); }
+    while let Some(node) = stack.pop() {
+        if ! (*node).sibling.is_null() { stack.push((*node).sibling); }
+        if ! (*node).child.is_null() { stack.push((*node).child); }
+        free(node);
+    }
+}
+
+/// An iterator over the elements of a `BinomialHeap` or a `PairingHeap`, in
+/// no particular order.
+pub struct Iter<'a, T: 'a> {
+    stack: Vec<*const Node<T>>,
+    n: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    // Iterate over the `n` nodes of the trees whose roots are linked through
+    // `sibling` from `first`, which must be owned by a heap that's borrowed
+    // for 'a.
+    fn new(first: Link<T>, n: usize) -> Iter<'a, T> {
+        let mut stack = vec![];
+        if ! first.is_null() { stack.push(first as *const _); }
+        Iter { stack, n, marker: PhantomData }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        // SAFETY: the nodes are owned by the heap, which is borrowed for 'a.
+        let node = unsafe { &*node };
+        if ! node.sibling.is_null() { self.stack.push(node.sibling); }
+        if ! node.child.is_null() { self.stack.push(node.child); }
+        self.n -= 1;
+        Some(&node.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{sink, swim};
+    use std::iter::FromIterator;
+    use super::{into_sorted_vec, sink_ops, swim_ops, Handle, MaxPQLike};
+    use super::binomial::BinomialHeap;
+    use super::pairing::PairingHeap;
+    use super::pq::MaxPQ;
+    use super::super::Ops;
+    use super::super::tests::random_permutation;
+
+    // The operations of a mergeable heap, so that the tests below can run
+    // over both `BinomialHeap` and `PairingHeap`. `check` checks the
+    // invariants of the heap.
+    pub trait Mergeable: Default + FromIterator<isize> + Extend<isize> {
+        fn insert(&mut self, value: isize) -> Handle<isize>;
+        fn pop(&mut self) -> Option<isize>;
+        fn peek(&self) -> Option<&isize>;
+        fn size(&self) -> usize;
+        fn elements(&self) -> Vec<isize>;
+        fn into_sorted_vec(self) -> Vec<isize>;
+        fn meld(&mut self, other: Self);
+        fn contains(&self, handle: &Handle<isize>) -> bool;
+        fn key_of(&self, handle: &Handle<isize>) -> Option<&isize>;
+        fn increase_key(&mut self, handle: &Handle<isize>, key: isize);
+        fn decrease_key(&mut self, handle: &Handle<isize>, key: isize);
+        fn delete(&mut self, handle: &Handle<isize>) -> isize;
+        fn check(&self);
+    }
+
+    pub fn test_works_for_1_element<H: Mergeable>() {
+        let mut heap = H::default();
+        assert_eq!(0, heap.size());
+        assert_eq!(None, heap.peek());
+        assert_eq!(None, heap.pop());
+
+        let h = heap.insert(42);
+        assert_eq!(1, heap.size());
+        assert_eq!(Some(&42), heap.peek());
+        assert_eq!(Some(&42), heap.key_of(&h));
+
+        assert_eq!(Some(42), heap.pop());
+        assert_eq!(0, heap.size());
+        assert!( ! heap.contains(&h));
+        assert_eq!(None, heap.key_of(&h));
+    }
+
+    pub fn test_pops_in_order<H: Mergeable>() {
+        let input = random_permutation(1000, 1);
+        let mut heap: H = input.iter().cloned().collect();
+        heap.check();
+        let elements = heap.elements();
+        assert_eq!(elements.len(), 1000);
+        assert_eq!(elements.iter().sum::<isize>(), 999*1000/2);
+        for i in (0..1000).rev() {
+            assert_eq!(heap.pop(), Some(i));
+            if i % 97 == 0 { heap.check(); }
+        }
+        assert_eq!(heap.pop(), None);
+
+        let heap: H = input.into_iter().collect();
+        assert_eq!(heap.into_sorted_vec(), (0..1000).collect::<Vec<_>>());
+    }
+
+    pub fn test_meld_works<H: Mergeable>() {
+        let mut a: H = vec![1, 5, 9, 13].into_iter().collect();
+        let mut b = H::default();
+        let handles: Vec<_> = [2, 6, 10, 14, 3].iter()
+                                               .map(|&x| b.insert(x))
+                                               .collect();
+        a.meld(b);
+        a.check();
+        assert_eq!(a.size(), 9);
+        assert_eq!(a.peek(), Some(&14));
+        assert!(handles.iter().all(|h| a.contains(h)));
+        a.increase_key(&handles[4], 20);
+        assert_eq!(a.pop(), Some(20));
+        a.meld(H::default());
+        assert_eq!(a.into_sorted_vec(), vec![1, 2, 5, 6, 9, 10, 13, 14]);
+
+        // meld many heaps, and a heap into an empty one
+        let mut heaps: Vec<H> =
+            (0..50).map(|i| (0..i).map(|j| 50*j + i).collect()).collect();
+        let mut all = H::default();
+        for heap in heaps.drain(..) { all.meld(heap); }
+        all.check();
+        let mut expected: Vec<isize> =
+            (0..50).flat_map(|i| (0..i).map(move |j| 50*j + i)).collect();
+        expected.sort();
+        assert_eq!(all.into_sorted_vec(), expected);
+    }
+
+    pub fn test_handles_change_and_delete_keys<H: Mergeable>() {
+        let input = random_permutation(500, 2);
+        let mut heap = H::default();
+        let handles: Vec<_> = input.iter().map(|&x| heap.insert(x)).collect();
+        // give the trees some depth before changing keys
+        let top: Vec<_> = (0..10).map(|_| heap.pop().unwrap()).collect();
+        heap.extend(top);
+        let mut keys = input.clone();
+        for (i, h) in handles.iter().enumerate() {
+            if ! heap.contains(h) { keys[i] = -1; continue; }
+            match i % 4 {
+                0 => { keys[i] += 1000; heap.increase_key(h, keys[i]); }
+                1 => { keys[i] -= 1000; heap.decrease_key(h, keys[i]); }
+                2 => { assert_eq!(heap.delete(h), keys[i]); keys[i] = -1; }
+                _ => {}
+            }
+            if i % 50 == 0 { heap.check(); }
+        }
+        for (i, h) in handles.iter().enumerate() {
+            assert_eq!(heap.key_of(h).cloned().unwrap_or(-1), keys[i]);
+        }
+        keys.retain(|&k| k != -1);
+        assert_eq!(heap.size(), keys.len() + 10);
+        let mut expected: Vec<isize> = keys;
+        expected.extend(490..500);
+        expected.sort_by(|a, b| b.cmp(a));
+        for &k in &expected {
+            assert_eq!(heap.peek(), Some(&k));
+            assert_eq!(heap.pop(), Some(k));
+        }
+        assert_eq!(heap.size(), 0);
+        assert!(handles.iter().all(|h| ! heap.contains(h)));
+    }
+
+    pub fn test_increase_key_needs_a_greater_key<H: Mergeable>() {
+        let mut heap = H::default();
+        let h = heap.insert(5);
+        heap.increase_key(&h, 4);
+    }
+
+    pub fn test_decrease_key_needs_a_smaller_key<H: Mergeable>() {
+        let mut heap = H::default();
+        let h = heap.insert(5);
+        heap.decrease_key(&h, 6);
+    }
+
+    pub fn test_handles_of_other_heaps_panic<H: Mergeable>() {
+        let mut a = H::default();
+        let mut b = H::default();
+        a.insert(1);
+        let h = b.insert(2);
+        a.delete(&h);
+    }
+
+    pub fn test_handles_of_popped_elements_panic<H: Mergeable>() {
+        let mut heap = H::default();
+        let h = heap.insert(1);
+        heap.pop();
+        heap.insert(2);
+        heap.decrease_key(&h, 0);
+    }
+
+    pub fn test_handles_outlive_the_heap<H: Mergeable>() {
+        let mut heap = H::default();
+        let handles: Vec<_> = (0..10).map(|x| heap.insert(x)).collect();
+        drop(heap);
+        let heap = H::default();
+        assert!(handles.iter().all(|h| ! heap.contains(h)));
+    }
+
+    pub fn test_handles_follow_melds<H: Mergeable>() {
+        let mut a = H::default();
+        let mut b = H::default();
+        let mut c = H::default();
+        let h = c.insert(1);
+        b.meld(c);
+        a.meld(b);
+        assert!(a.contains(&h));
+        assert_eq!(a.delete(&h), 1);
+        assert!( ! a.contains(&h));
+    }
+
+    fn test_max_pq_like<Q: MaxPQLike<isize> + Default>() {
+        let mut pq = Q::default();
+        assert!(pq.is_empty());
+        assert_eq!(pq.peek(), None);
+        for x in random_permutation(100, 3) {
+            pq.insert(x);
+        }
+        assert_eq!(pq.size(), 100);
+        assert_eq!(pq.peek(), Some(&99));
+        assert_eq!(pq.pop(), Some(99));
+        assert_eq!(into_sorted_vec(pq), (0..99).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn max_pq_like_works() {
+        test_max_pq_like::<MaxPQ<isize>>();
+        test_max_pq_like::<BinomialHeap<isize>>();
+        test_max_pq_like::<PairingHeap<isize>>();
+    }
+
+    fn swim<T: Ord>(v: &mut [T], i: usize) {
+        swim_d(v, i, 2);
+    }
+
+    fn sink<T: Ord>(v: &mut [T], i: usize, n: usize) {
+        sink_d(v, i, n, 2);
+    }
+
+    fn swim_d<T: Ord>(v: &mut [T], i: usize, d: usize) {
+        swim_ops(v, i, d, &mut Ops::new(|a: &T, b: &T| a.cmp(b), &mut ()));
+    }
+
+    fn sink_d<T: Ord>(v: &mut [T], i: usize, n: usize, d: usize) {
+        sink_ops(v, i, n, d, &mut Ops::new(|a: &T, b: &T| a.cmp(b), &mut ()));
+    }
 
     #[test]
     fn swim_works() {
@@ -63,4 +549,37 @@ mod tests {
         assert_eq!(vec![9,7,4,6,5,3,1], v);
 
     }
+
+    #[test]
+    fn swim_and_sink_work_for_3_ary_heaps() {
+        let mut v = vec![10,6,9,4,3,5,2,1,8,7,20];
+        swim_d(&mut v, 10, 3);
+        assert_eq!(vec![20,6,9,10,3,5,2,1,8,7,4], v);
+
+        let mut v = vec![5,6,9,4,3,2,1,1,8,7];
+        sink_d(&mut v, 0, 10, 3);
+        assert_eq!(vec![9,6,8,4,3,2,1,1,5,7], v);
+    }
+
+    fn is_heap(v: &[isize], d: usize) -> bool {
+        (1..v.len()).all(|i| v[(i - 1) / d] >= v[i])
+    }
+
+    #[test]
+    fn heap_construction_works_for_any_arity() {
+        for &d in &[2, 3, 4, 8] {
+            let mut v = random_permutation(1000, d);
+            for k in (0..v.len()).rev() {
+                sink_d(&mut v, k, 1000, d);
+            }
+            assert!(is_heap(&v, d));
+
+            let mut v = vec![];
+            for (i, x) in random_permutation(1000, d).into_iter().enumerate() {
+                v.push(x);
+                swim_d(&mut v, i, d);
+            }
+            assert!(is_heap(&v, d));
+        }
+    }
 }
@@ -0,0 +1,125 @@
+//! Multiway merge (page 322).
+//!
+//! `Multiway` merges any number of sorted iterators into one sorted stream.
+//! An indexed priority queue holds the next element of each input, at the
+//! index of that input, so each element of the output takes O(log k) compares
+//! for k inputs, and only k elements are held in memory at a time.
+
+use super::index_pq::IndexMinPQ;
+
+/// An iterator that merges sorted iterators into one sorted iterator. Equal
+/// elements come out in the order of the iterators that they come from.
+pub struct Multiway<I: Iterator> where I::Item: Ord {
+    inputs: Vec<I>,
+    // the next element of each input, with the index of the input to break
+    // ties
+    pq: IndexMinPQ<(I::Item, usize)>,
+}
+
+impl<I: Iterator> Multiway<I> where I::Item: Ord {
+    /// Constructs an iterator that merges `inputs`, each of which must be in
+    /// ascending order.
+    pub fn new<J, K>(inputs: J) -> Multiway<I>
+        where J: IntoIterator<Item = K>, K: IntoIterator<IntoIter = I> {
+        let mut inputs: Vec<I> =
+            inputs.into_iter().map(|input| input.into_iter()).collect();
+        let mut pq = IndexMinPQ::new(inputs.len());
+        for (i, input) in inputs.iter_mut().enumerate() {
+            if let Some(value) = input.next() {
+                pq.insert(i, (value, i));
+            }
+        }
+        Multiway { inputs, pq }
+    }
+}
+
+impl<I: Iterator> Iterator for Multiway<I> where I::Item: Ord {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let (i, (value, _)) = self.pq.pop_min()?;
+        if let Some(next) = self.inputs[i].next() {
+            self.pq.insert(i, (next, i));
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.pq.size();
+        self.inputs.iter().fold((n, Some(n)), |(lo, hi), input| {
+            let (l, h) = input.size_hint();
+            (lo.saturating_add(l),
+             hi.and_then(|hi| h.and_then(|h| hi.checked_add(h))))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use super::Multiway;
+    use super::super::super::tests::random_permutation;
+
+    #[test]
+    fn multiway_merges_sorted_iterators() {
+        let inputs = vec![vec![1, 4, 7, 10], vec![], vec![2, 3, 11],
+                          vec![0, 5, 6, 8, 9]];
+        let merged = Multiway::new(inputs);
+        assert_eq!(merged.size_hint(), (12, Some(12)));
+        assert_eq!(merged.collect::<Vec<_>>(), (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn multiway_with_no_inputs() {
+        let inputs: Vec<Vec<isize>> = vec![];
+        assert_eq!(Multiway::new(inputs).count(), 0);
+        assert_eq!(Multiway::new(vec![vec![0; 0]; 3]).count(), 0);
+    }
+
+    #[test]
+    fn multiway_merges_many_streams() {
+        // deal a permutation into 100 sorted shards
+        let mut shards = vec![vec![]; 100];
+        for (i, x) in random_permutation(10000, 1).into_iter().enumerate() {
+            shards[i % 100].push(x);
+        }
+        for shard in &mut shards { shard.sort(); }
+        let merged: Vec<isize> = Multiway::new(shards).collect();
+        assert_eq!(merged, (0..10000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn multiway_is_stable() {
+        let a = vec![(1, 'a'), (2, 'a'), (2, 'b')];
+        let b = vec![(1, 'c'), (2, 'c')];
+        let keys = |v: Vec<(isize, char)>| {
+            v.into_iter().map(|(k, c)| Key(k, c)).collect::<Vec<_>>()
+        };
+        let merged: Vec<_> = Multiway::new(vec![keys(a), keys(b)])
+                                     .map(|Key(k, c)| (k, c))
+                                     .collect();
+        assert_eq!(merged, vec![(1, 'a'), (1, 'c'), (2, 'a'), (2, 'b'),
+                                (2, 'c')]);
+    }
+
+    // An element ordered by its key alone.
+    struct Key(isize, char);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Key) -> bool { self.0 == other.0 }
+    }
+
+    impl Eq for Key {}
+
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Key) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Key {
+        fn cmp(&self, other: &Key) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+}
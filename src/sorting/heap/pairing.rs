@@ -0,0 +1,477 @@
+//! Pairing heaps (Fredman, Sedgewick, Sleator and Tarjan, 1986).
+//!
+//! A pairing heap is a single heap-ordered tree with any number of children
+//! per node. Melding links two roots, making the smaller one the first child
+//! of the larger, so `meld` and `insert` take constant time. `pop` links the
+//! children of the root in pairs from left to right and then links the pairs
+//! from right to left, which takes O(log N) amortized time. `increase_key`
+//! cuts the node out of the tree and links it with the root, in o(log N)
+//! amortized time; `decrease_key` and `delete` also meld the node's children.
+//!
+//! The nodes are shared with `BinomialHeap`, and linked with raw pointers.
+//! All `unsafe` blocks in this module rely on the same invariant:
+//!
+//! - `root` is null if the heap is empty; otherwise it points to the root of
+//!   the tree, whose `up` and `sibling` are null.
+//! - The `child` of a node points to its first child, and its children are
+//!   linked through `sibling`. The `up` of a child points to its previous
+//!   sibling, or to its parent if it's the first child.
+//! - No child has a greater key than its parent.
+//! - Every node reachable from `root` was allocated by `new_node` and is owned
+//!   exclusively by the heap, which holds `n` of them.
+//! - The slot of every node points back at the node, and the slot of every
+//!   element that has left the heap is null.
+
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use super::{free, free_trees, into_sorted_vec, new_node, Link, MaxPQLike,
+            Node, Owner};
+pub use super::{Handle, Iter};
+
+/// A priority queue based on a pairing heap that removes the largest element
+/// first, with the same interface as `MaxPQ` and also fast melding and
+/// changing of keys through handles.
+pub struct PairingHeap<T: Ord> {
+    root: Link<T>,
+    n: usize,
+    owner: Rc<Owner>,
+    // the heap owns its nodes
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Constructs a new, empty heap.
+    pub fn new() -> PairingHeap<T> {
+        PairingHeap {
+            root: ptr::null_mut(),
+            n: 0,
+            owner: Owner::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Constructs a heap that holds the elements of `v`, in linear time.
+    pub fn from_vec(v: Vec<T>) -> PairingHeap<T> {
+        let mut heap = PairingHeap::new();
+        heap.extend(v);
+        heap
+    }
+
+    /// Adds an element to the heap and returns a handle to it.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let (node, handle) = new_node(value, &self.owner);
+        self.n += 1;
+        // SAFETY: `node` is a new tree that's owned by the heap.
+        unsafe { self.add_tree(node); }
+        handle
+    }
+
+    /// Removes the largest element and returns it, or `None` if the heap is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.root.is_null() { return None; }
+        let node = self.root;
+        self.n -= 1;
+        // SAFETY: `root` points to the root of the heap, and once it's
+        // removed nothing else refers to it.
+        unsafe {
+            self.remove(node);
+            Some(free(node))
+        }
+    }
+
+    /// Removes the largest element and returns it, or `None` if the heap is
+    /// empty. The same as `pop`.
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    /// Returns a reference to the largest element, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: `root` is null or points to a node owned by the heap.
+        unsafe { self.root.as_ref() }.map(|node| &node.key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Returns an iterator over the elements of the heap, in no particular
+    /// order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.root, self.n)
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        into_sorted_vec(self)
+    }
+
+    /// Moves all the elements of `other` into this heap, in constant time.
+    /// Handles to the elements of `other` refer to them in this heap.
+    pub fn meld(&mut self, mut other: PairingHeap<T>) {
+        Owner::join(&other.owner, &self.owner);
+        let root = mem::replace(&mut other.root, ptr::null_mut());
+        self.n += mem::replace(&mut other.n, 0);
+        // SAFETY: the tree of `other` is now owned by this heap.
+        unsafe { self.add_tree(root); }
+    }
+
+    /// Returns `true` if the element that `handle` refers to is in the heap.
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        handle.node_in(&self.owner).is_some()
+    }
+
+    /// Returns a reference to the element that `handle` refers to, or `None`
+    /// if it isn't in the heap.
+    pub fn key_of(&self, handle: &Handle<T>) -> Option<&T> {
+        // SAFETY: the slot points to a node owned by the heap.
+        handle.node_in(&self.owner).map(|node| unsafe { &(*node).key })
+    }
+
+    /// Increases the element that `handle` refers to to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap, or if `key` isn't greater
+    /// than it.
+    pub fn increase_key(&mut self, handle: &Handle<T>, key: T) {
+        let node = handle.node_of(&self.owner);
+        // SAFETY: `node` is owned by the heap; once it's cut out of the tree
+        // its subtree is a tree of its own.
+        unsafe {
+            assert!(key > (*node).key,
+                    "increase_key: key isn't greater than the current key");
+            (*node).key = key;
+            if node != self.root {
+                cut(node);
+                self.add_tree(node);
+            }
+        }
+    }
+
+    /// Decreases the element that `handle` refers to to `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap, or if `key` isn't less than
+    /// it.
+    pub fn decrease_key(&mut self, handle: &Handle<T>, key: T) {
+        let node = handle.node_of(&self.owner);
+        // SAFETY: `node` is owned by the heap, and after it's removed it's a
+        // node on its own.
+        unsafe {
+            assert!(key < (*node).key,
+                    "decrease_key: key isn't less than the current key");
+            self.remove(node);
+            (*node).key = key;
+            self.add_tree(node);
+        }
+    }
+
+    /// Removes the element that `handle` refers to and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element isn't in the heap.
+    pub fn delete(&mut self, handle: &Handle<T>) -> T {
+        let node = handle.node_of(&self.owner);
+        self.n -= 1;
+        // SAFETY: `node` is owned by the heap, and once it's removed nothing
+        // else refers to it.
+        unsafe {
+            self.remove(node);
+            free(node)
+        }
+    }
+
+    // Link the tree rooted at `tree`, if any, with the root of the heap.
+    unsafe fn add_tree(&mut self, tree: Link<T>) {
+        if tree.is_null() { return; }
+        self.root = if self.root.is_null() { tree }
+                    else                   { link(self.root, tree) };
+    }
+
+    // Remove `node` from the tree, leaving it on its own, and meld its
+    // children back into the heap.
+    unsafe fn remove(&mut self, node: Link<T>) {
+        if node == self.root {
+            self.root = ptr::null_mut();
+        } else {
+            cut(node);
+        }
+        let children = mem::replace(&mut (*node).child, ptr::null_mut());
+        let tree = combine(children);
+        self.add_tree(tree);
+    }
+}
+
+// Link the roots `a` and `b`, making the one with the smaller key the first
+// child of the other, and return the new root.
+unsafe fn link<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (parent, child) = if (*a).key >= (*b).key { (a, b) } else { (b, a) };
+    let first = (*parent).child;
+    (*child).sibling = first;
+    if ! first.is_null() { (*first).up = child; }
+    (*child).up = parent;
+    (*parent).child = child;
+    parent
+}
+
+// Cut the subtree rooted at `node`, which isn't the root, out of the tree.
+unsafe fn cut<T>(node: Link<T>) {
+    let prev = (*node).up;
+    if (*prev).child == node {
+        (*prev).child = (*node).sibling;
+    } else {
+        (*prev).sibling = (*node).sibling;
+    }
+    if ! (*node).sibling.is_null() { (*(*node).sibling).up = prev; }
+    (*node).sibling = ptr::null_mut();
+    (*node).up = ptr::null_mut();
+}
+
+// Combine the list of siblings that starts at `first` into one tree, by
+// linking them in pairs from left to right and then linking the pairs from
+// right to left. Returns the root, or null if there are no siblings.
+unsafe fn combine<T: Ord>(first: Link<T>) -> Link<T> {
+    let mut pairs = vec![];
+    let mut x = first;
+    while ! x.is_null() {
+        let a = x;
+        let b = (*a).sibling;
+        (*a).sibling = ptr::null_mut();
+        (*a).up = ptr::null_mut();
+        if b.is_null() {
+            pairs.push(a);
+            break;
+        }
+        x = (*b).sibling;
+        (*b).sibling = ptr::null_mut();
+        (*b).up = ptr::null_mut();
+        pairs.push(link(a, b));
+    }
+    let mut root = match pairs.pop() {
+        Some(tree) => tree,
+        None => return ptr::null_mut(),
+    };
+    while let Some(tree) = pairs.pop() {
+        root = link(tree, root);
+    }
+    root
+}
+
+impl<T: Ord> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        // SAFETY: the heap owns the nodes reachable from `root`.
+        unsafe { free_trees(self.root); }
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> PairingHeap<T> {
+        PairingHeap::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PairingHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> PairingHeap<T> {
+        PairingHeap::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> Extend<T> for PairingHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord> MaxPQLike<T> for PairingHeap<T> {
+    fn insert(&mut self, value: T) {
+        PairingHeap::insert(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        PairingHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        PairingHeap::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        PairingHeap::is_empty(self)
+    }
+
+    fn size(&self) -> usize {
+        PairingHeap::size(self)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a PairingHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Handle, Link, PairingHeap};
+    use super::super::Slot;
+    use super::super::tests::{test_works_for_1_element, test_pops_in_order,
+                              test_meld_works,
+                              test_handles_change_and_delete_keys,
+                              test_increase_key_needs_a_greater_key,
+                              test_decrease_key_needs_a_smaller_key,
+                              test_handles_of_other_heaps_panic,
+                              test_handles_of_popped_elements_panic,
+                              test_handles_outlive_the_heap,
+                              test_handles_follow_melds, Mergeable};
+
+    impl Mergeable for PairingHeap<isize> {
+        fn insert(&mut self, value: isize) -> Handle<isize> {
+            PairingHeap::insert(self, value)
+        }
+
+        fn pop(&mut self) -> Option<isize> {
+            PairingHeap::pop(self)
+        }
+
+        fn peek(&self) -> Option<&isize> {
+            PairingHeap::peek(self)
+        }
+
+        fn size(&self) -> usize {
+            PairingHeap::size(self)
+        }
+
+        fn elements(&self) -> Vec<isize> {
+            self.into_iter().cloned().collect()
+        }
+
+        fn into_sorted_vec(self) -> Vec<isize> {
+            PairingHeap::into_sorted_vec(self)
+        }
+
+        fn meld(&mut self, other: PairingHeap<isize>) {
+            PairingHeap::meld(self, other)
+        }
+
+        fn contains(&self, handle: &Handle<isize>) -> bool {
+            PairingHeap::contains(self, handle)
+        }
+
+        fn key_of(&self, handle: &Handle<isize>) -> Option<&isize> {
+            PairingHeap::key_of(self, handle)
+        }
+
+        fn increase_key(&mut self, handle: &Handle<isize>, key: isize) {
+            PairingHeap::increase_key(self, handle, key)
+        }
+
+        fn decrease_key(&mut self, handle: &Handle<isize>, key: isize) {
+            PairingHeap::decrease_key(self, handle, key)
+        }
+
+        fn delete(&mut self, handle: &Handle<isize>) -> isize {
+            PairingHeap::delete(self, handle)
+        }
+
+        // Check the invariants of the heap.
+        fn check(&self) {
+            unsafe fn check_tree(node: Link<isize>) -> usize {
+                let slot: &Slot<_> = &(*node).slot;
+                assert!(slot.node.get() == node);
+                let mut size = 1;
+                let mut up = node;
+                let mut child = (*node).child;
+                while ! child.is_null() {
+                    assert!((*child).up == up);
+                    assert!((*child).key <= (*node).key);
+                    size += check_tree(child);
+                    up = child;
+                    child = (*child).sibling;
+                }
+                size
+            }
+
+            if self.root.is_null() {
+                assert_eq!(self.size(), 0);
+                return;
+            }
+            unsafe {
+                assert!((*self.root).up.is_null());
+                assert!((*self.root).sibling.is_null());
+                assert_eq!(check_tree(self.root), self.size());
+            }
+        }
+    }
+
+    #[test]
+    fn works_for_1_element() {
+        test_works_for_1_element::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    fn pops_in_order() {
+        test_pops_in_order::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    fn meld_works() {
+        test_meld_works::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_change_and_delete_keys() {
+        test_handles_change_and_delete_keys::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't greater")]
+    fn increase_key_needs_a_greater_key() {
+        test_increase_key_needs_a_greater_key::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't less")]
+    fn decrease_key_needs_a_smaller_key() {
+        test_decrease_key_needs_a_smaller_key::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't in the heap")]
+    fn handles_of_other_heaps_panic() {
+        test_handles_of_other_heaps_panic::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't in the heap")]
+    fn handles_of_popped_elements_panic() {
+        test_handles_of_popped_elements_panic::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_outlive_the_heap() {
+        test_handles_outlive_the_heap::<PairingHeap<isize>>();
+    }
+
+    #[test]
+    fn handles_follow_melds() {
+        test_handles_follow_melds::<PairingHeap<isize>>();
+    }
+}
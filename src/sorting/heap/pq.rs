@@ -1,59 +1,371 @@
 //! Algorithm 2.6 Heap priority queue
 
-use super::{sink, swim};
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
 
-pub struct MaxPQ<T: Ord>(Vec<T>);
+use super::{check_arity, reverse_cmp, sink_ops, swim_ops, Compare,
+            MaxPQLike, DEFAULT_ARITY};
+use super::super::Ops;
+
+/// A priority queue based on a heap, with the order defined by a comparison
+/// function: `pop` removes the greatest element according to `compare`. The
+/// heap is binary unless another arity is chosen with `with_arity`.
+pub struct PriorityQueue<T, F> {
+    v: Vec<T>,
+    // the number of children of each node
+    d: usize,
+    compare: F,
+}
+
+impl<T, F> PriorityQueue<T, F> where F: FnMut(&T, &T) -> Ordering {
+    /// Constructs a new, empty priority queue ordered by `compare`.
+    pub fn with_comparator(compare: F) -> PriorityQueue<T, F> {
+        PriorityQueue::with_arity(DEFAULT_ARITY, compare)
+    }
+
+    /// Constructs a new, empty priority queue ordered by `compare`, based on a
+    /// `d`-ary heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `d` is less than 2.
+    pub fn with_arity(d: usize, compare: F) -> PriorityQueue<T, F> {
+        check_arity(d);
+        PriorityQueue { v: vec![], d, compare }
+    }
+
+    /// Constructs a priority queue ordered by `compare` that holds the
+    /// elements of `v`. Uses the bottom-up heap construction, which takes
+    /// fewer than 2N compares.
+    pub fn from_vec_with_comparator(mut v: Vec<T>, mut compare: F)
+        -> PriorityQueue<T, F> {
+        let n = v.len();
+        let d = DEFAULT_ARITY;
+        for k in (0..n/2).rev() {
+            sink_ops(&mut v, k, n, d, &mut Ops::new(&mut compare, &mut ()));
+        }
+        PriorityQueue { v, d, compare }
+    }
+
+    /// Adds an element to the queue.
+    pub fn insert(&mut self, value: T) {
+        let n = self.v.len();
+        self.v.push(value);
+        swim_ops(&mut self.v, n, self.d,
+                 &mut Ops::new(&mut self.compare, &mut ()));
+    }
+
+    /// Removes the greatest element and returns it, or `None` if the queue is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let n = self.v.len();
+        if n == 0 { return None; }
+        self.v.swap(0, n-1);
+        let result = self.v.pop();
+        sink_ops(&mut self.v, 0, n-1, self.d,
+                 &mut Ops::new(&mut self.compare, &mut ()));
+        result
+    }
+
+    /// Adds `value` to the queue, then removes the greatest element and
+    /// returns it. Takes a single sink instead of the swim and sink of an
+    /// `insert` followed by a `pop`, and none if `value` is the greatest.
+    pub fn push_pop(&mut self, value: T) -> T {
+        if self.v.is_empty() ||
+           (self.compare)(&value, &self.v[0]) != Ordering::Less {
+            return value;
+        }
+        let top = mem::replace(&mut self.v[0], value);
+        let n = self.v.len();
+        sink_ops(&mut self.v, 0, n, self.d,
+                 &mut Ops::new(&mut self.compare, &mut ()));
+        top
+    }
+
+    /// Returns a reference to the greatest element, or `None` if the queue is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.v.first()
+    }
+
+    /// Returns `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.v.is_empty()
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn size(&self) -> usize {
+        self.v.len()
+    }
+
+    /// Returns an iterator over the elements of the queue, in heap order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.v.iter()
+    }
+
+    /// Consumes the queue and returns its elements in heap order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.v
+    }
+
+    /// Consumes the queue and returns its elements in ascending order
+    /// according to `compare`, using the sortdown phase of heapsort.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let PriorityQueue { mut v, d, mut compare } = self;
+        let mut n = v.len();
+        while n > 1 {
+            n -= 1;
+            v.swap(0, n);
+            sink_ops(&mut v, 0, n, d, &mut Ops::new(&mut compare, &mut ()));
+        }
+        v
+    }
+}
+
+impl<T, F> Extend<T> for PriorityQueue<T, F>
+    where F: FnMut(&T, &T) -> Ordering {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// A priority queue that removes the largest element first.
+pub struct MaxPQ<T: Ord>(PriorityQueue<T, Compare<T>>);
 
 impl <T: Ord> MaxPQ<T> {
+    /// Constructs a new, empty priority queue.
     pub fn new() -> MaxPQ<T> {
-        MaxPQ(vec![])
+        MaxPQ(PriorityQueue::with_comparator(T::cmp))
+    }
+
+    /// Constructs a new, empty priority queue based on a `d`-ary heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `d` is less than 2.
+    pub fn with_arity(d: usize) -> MaxPQ<T> {
+        MaxPQ(PriorityQueue::with_arity(d, T::cmp))
     }
 
+    /// Constructs a priority queue that holds the elements of `v`, in linear
+    /// time.
+    pub fn from_vec(v: Vec<T>) -> MaxPQ<T> {
+        MaxPQ(PriorityQueue::from_vec_with_comparator(v, T::cmp))
+    }
+
+    /// Adds an element to the queue.
     pub fn insert(&mut self, value: T) {
-        let MaxPQ(ref mut v) = *self;
-        let n = v.len();
-        v.push(value);
-        swim(v, n);
+        self.0.insert(value);
+    }
+
+    /// Removes the largest element and returns it, or `None` if the queue is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop()
     }
 
+    /// Removes the largest element and returns it, or `None` if the queue is
+    /// empty. The same as `pop`.
     pub fn pop_max(&mut self) -> Option<T> {
-        let MaxPQ(ref mut v) = *self;
-        let n = v.len() - 1;
-        v.swap(0, n);
-        let result = v.pop();
-        let n = v.len();
-        sink(v, 0, n);
-        result
+        self.0.pop()
+    }
+
+    /// Returns a reference to the largest element, or `None` if the queue is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.0.peek()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    /// Returns an iterator over the elements of the queue, in heap order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Consumes the queue and returns its elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.0.into_sorted_vec()
+    }
+}
+
+impl<T: Ord> Default for MaxPQ<T> {
+    fn default() -> MaxPQ<T> {
+        MaxPQ::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MaxPQ<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> MaxPQ<T> {
+        MaxPQ::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> Extend<T> for MaxPQ<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<T: Ord> MaxPQLike<T> for MaxPQ<T> {
+    fn insert(&mut self, value: T) {
+        MaxPQ::insert(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        MaxPQ::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        MaxPQ::peek(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        MaxPQ::is_empty(self)
+    }
+
+    fn size(&self) -> usize {
+        MaxPQ::size(self)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a MaxPQ<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// A priority queue that removes the smallest element first.
+pub struct MinPQ<T: Ord>(PriorityQueue<T, Compare<T>>);
+
+impl <T: Ord> MinPQ<T> {
+    /// Constructs a new, empty priority queue.
+    pub fn new() -> MinPQ<T> {
+        MinPQ(PriorityQueue::with_comparator(reverse_cmp))
+    }
+
+    /// Constructs a new, empty priority queue based on a `d`-ary heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `d` is less than 2.
+    pub fn with_arity(d: usize) -> MinPQ<T> {
+        MinPQ(PriorityQueue::with_arity(d, reverse_cmp))
+    }
+
+    /// Constructs a priority queue that holds the elements of `v`, in linear
+    /// time.
+    pub fn from_vec(v: Vec<T>) -> MinPQ<T> {
+        MinPQ(PriorityQueue::from_vec_with_comparator(v, reverse_cmp))
+    }
+
+    /// Adds an element to the queue.
+    pub fn insert(&mut self, value: T) {
+        self.0.insert(value);
+    }
+
+    /// Removes the smallest element and returns it, or `None` if the queue is
+    /// empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Removes the smallest element and returns it, or `None` if the queue is
+    /// empty. The same as `pop`.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Returns a reference to the smallest element, or `None` if the queue is
+    /// empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.0.peek()
     }
 
     pub fn is_empty(&self) -> bool {
-        let MaxPQ(ref v) = *self;
-        v.is_empty()
+        self.0.is_empty()
     }
 
     pub fn size(&self) -> usize {
-        let MaxPQ(ref v) = *self;
-        v.len()
+        self.0.size()
+    }
+
+    /// Returns an iterator over the elements of the queue, in heap order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Consumes the queue and returns its elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut v = self.0.into_sorted_vec();
+        v.reverse();
+        v
+    }
+}
+
+impl<T: Ord> Default for MinPQ<T> {
+    fn default() -> MinPQ<T> {
+        MinPQ::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinPQ<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> MinPQ<T> {
+        MinPQ::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> Extend<T> for MinPQ<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a MinPQ<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::MaxPQ;
+    use std::cell::Cell;
+    use super::{MaxPQ, MinPQ, PriorityQueue};
+    use super::super::super::tests::random_permutation;
 
     #[test]
     fn priority_queue_works_for_1_element() {
         let mut pq = MaxPQ::new();
         assert!(pq.is_empty());
         assert_eq!(0, pq.size());
+        assert_eq!(None, pq.peek());
+        assert_eq!(None, pq.pop_max());
 
         pq.insert(42);
         assert!( ! pq.is_empty());
         assert_eq!(1, pq.size());
+        assert_eq!(Some(&42), pq.peek());
 
         assert_eq!(Some(42), pq.pop_max());
         assert!(pq.is_empty());
         assert_eq!(0, pq.size());
+        assert_eq!(None, pq.pop_max());
     }
 
     #[test]
@@ -74,4 +386,102 @@ mod tests {
         assert!(pq.is_empty());
         assert_eq!(0, pq.size());
     }
+
+    #[test]
+    fn min_priority_queue_works() {
+        let mut pq = MinPQ::new();
+        assert_eq!(None, pq.pop());
+        pq.extend(vec![5,2,8,7,4,9,0,1,3,6]);
+        assert_eq!(Some(&0), pq.peek());
+        let mut output = vec![];
+        while let Some(x) = pq.pop_min() {
+            output.push(x);
+        }
+        assert_eq!(output, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn priority_queues_work_for_any_arity() {
+        for &d in &[2, 3, 4, 8] {
+            let input = random_permutation(500, d);
+            let mut max_pq = MaxPQ::with_arity(d);
+            let mut min_pq = MinPQ::with_arity(d);
+            max_pq.extend(input.iter().cloned());
+            min_pq.extend(input.iter().cloned());
+            for i in 0..500 {
+                assert_eq!(max_pq.pop(), Some(499 - i));
+                assert_eq!(min_pq.pop(), Some(i));
+            }
+            assert_eq!(max_pq.pop(), None);
+
+            let mut pq = MinPQ::with_arity(d);
+            pq.extend(input);
+            assert_eq!(pq.into_sorted_vec(), (0..500).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn from_vec_builds_heap_in_linear_compares() {
+        let n = 10000;
+        let compares = Cell::new(0);
+        let mut pq = PriorityQueue::from_vec_with_comparator(
+            random_permutation(n, 1),
+            |a: &isize, b: &isize| {
+                compares.set(compares.get() + 1);
+                a.cmp(b)
+            });
+        assert!(compares.get() < 2*n);
+        assert_eq!(pq.size(), n);
+        for i in (0..n as isize).rev() {
+            assert_eq!(pq.pop(), Some(i));
+        }
+        assert_eq!(pq.pop(), None);
+    }
+
+    #[test]
+    fn push_pop_works() {
+        let mut pq = PriorityQueue::with_comparator(|a: &isize, b| a.cmp(b));
+        assert_eq!(pq.push_pop(5), 5);
+        assert!(pq.is_empty());
+        pq.extend(vec![3, 8, 1]);
+        assert_eq!(pq.push_pop(9), 9);
+        assert_eq!(pq.push_pop(8), 8);
+        assert_eq!(pq.push_pop(2), 8);
+        assert_eq!(pq.size(), 3);
+        assert_eq!(pq.into_sorted_vec(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn into_sorted_vec_works() {
+        let input = random_permutation(100, 2);
+        let sorted: Vec<isize> = (0..100).collect();
+        assert_eq!(MaxPQ::from_vec(input.clone()).into_sorted_vec(), sorted);
+        assert_eq!(MinPQ::from_vec(input.clone()).into_sorted_vec(), sorted);
+        let pq: MinPQ<isize> = input.into_iter().collect();
+        assert_eq!(pq.size(), 100);
+    }
+
+    #[test]
+    fn iter_visits_every_element() {
+        let pq: MaxPQ<isize> = random_permutation(50, 3).into_iter().collect();
+        assert_eq!(pq.iter().next(), Some(&49));
+        let mut seen: Vec<isize> = (&pq).into_iter().cloned().collect();
+        seen.sort();
+        assert_eq!(seen, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn priority_queue_with_comparator() {
+        // shortest word first, ties broken alphabetically
+        let mut pq = PriorityQueue::with_comparator(|a: &&str, b: &&str| {
+            b.len().cmp(&a.len()).then_with(|| b.cmp(a))
+        });
+        pq.extend("it was the best of times".split(' '));
+        assert_eq!(pq.peek(), Some(&"it"));
+        let mut output = vec![];
+        while let Some(w) = pq.pop() {
+            output.push(w);
+        }
+        assert_eq!(output, vec!["it", "of", "the", "was", "best", "times"]);
+    }
 }
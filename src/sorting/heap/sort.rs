@@ -1,27 +1,154 @@
 //! Algorithm 2.7 Heapsort
 
-use super::sink;
+use std::cmp::Ordering;
+
+use super::{check_arity, sink_ops, DEFAULT_ARITY};
+use super::super::{Ops, Sorter};
+use super::super::stats::{Recorder, SortStats};
+
+/// Heapsort is not stable: the heap order says nothing about the relative
+/// order of equal elements.
+pub const STABLE: bool = false;
 
 /// Sort `v` using heapsort.
-pub fn sort<T: Ord>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using heapsort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using heapsort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using heapsort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Sort `v` using heapsort on a `d`-ary heap, in which every node has `d`
+/// children. A larger `d` makes the heap shallower, so elements move fewer
+/// times, at the cost of more compares to find the largest child.
+///
+/// # Panics
+///
+/// Panics if `d` is less than 2.
+pub fn sort_with_arity<T: Ord>(v: &mut [T], d: usize) {
+    check_arity(d);
+    sort_dary_ops(v, d, &mut Ops::new(|a: &T, b: &T| a.cmp(b), &mut ()));
+}
+
+/// Heapsort as a `Sorter`.
+pub struct Heap;
+
+impl<T: Ord> Sorter<T> for Heap {
+    fn name(&self) -> &'static str { "heap" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+// Sort `v`, using `ops` for compares and exchanges.
+pub(in sorting) fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    sort_dary_ops(v, DEFAULT_ARITY, ops);
+}
+
+// Sort `v` using a `d`-ary heap.
+fn sort_dary_ops<T, F, R>(v: &mut [T], d: usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
-    for k in (0..n/2).rev() {
-        sink(v, k, n);
+    // the last node with children is the parent of `v[n-1]`
+    for k in (0..(n + d - 2)/d).rev() {
+        sink_ops(v, k, n, d, ops);
     }
     for k in (1..n).rev() {
-        v.swap(0, k);
-        sink(v, 0, k);
+        ops.exch(v, 0, k);
+        sink_ops(v, 0, k, d, ops);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::super::tests::test_sort;
+    use super::{sort, sort_by, sort_by_key, sort_dary_ops, sort_with_arity,
+                sort_with_stats, STABLE};
+    use super::super::super::Ops;
+    use super::super::super::stats::SortStats;
+    use super::super::super::tests::{lg, random_permutation, stats_for,
+                                     test_sort, test_sort_by_key,
+                                     test_sort_descending, test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
-}
 
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_with_stats_counts_compares_and_exchanges() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        let n_lg_n = n as f64 * lg(n);
+        assert!((stats.compares as f64) < 2.0 * n_lg_n + 2.0 * n as f64);
+        assert!((stats.exchanges as f64) < n_lg_n + n as f64);
+    }
+
+    #[test]
+    fn sort_with_arity_works() {
+        for &d in &[2, 3, 4, 8] {
+            test_sort(|v| sort_with_arity(v, d));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "arity")]
+    fn sort_with_arity_1_panics() {
+        sort_with_arity(&mut [2, 1], 1);
+    }
+
+    #[test]
+    fn higher_arity_takes_fewer_exchanges() {
+        let n = 10000;
+        let input = random_permutation(n, 2);
+        let stats: Vec<SortStats> = [2, 4, 8].iter().map(|&d| {
+            let mut v = input.clone();
+            let mut stats = SortStats::new();
+            let mut ops = Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut stats);
+            sort_dary_ops(&mut v, d, &mut ops);
+            assert_eq!(v, (0..n as isize).collect::<Vec<_>>());
+            stats
+        }).collect();
+
+        // about N log_d N exchanges and (d-1) N log_d N compares, but about
+        // half of the sinks go all the way down
+        let n_lg_n = n as f64 * lg(n);
+        assert!(stats[0].exchanges > stats[1].exchanges);
+        assert!(stats[1].exchanges > stats[2].exchanges);
+        assert!((stats[2].exchanges as f64) < n_lg_n / 3.0 + n as f64);
+        assert!(stats[0].compares < stats[2].compares);
+    }
+
+    #[test]
+    fn stability_matches_marker() {
+        test_stability(|v| sort_by_key(v, |r| r.0), STABLE);
+    }
+}
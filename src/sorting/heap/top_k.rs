@@ -0,0 +1,149 @@
+//! Top-k selection from a stream (TopM, page 311).
+//!
+//! To keep the k largest elements of a stream, `TopK` holds them in a
+//! priority queue that removes the smallest first: an element that's larger
+//! than the smallest one kept replaces it. That takes O(N log k) time and
+//! O(k) space for a stream of N elements, however large N is.
+
+use std::slice;
+
+use super::{reverse_cmp, Compare};
+use super::pq::PriorityQueue;
+
+/// The k largest or the k smallest elements of a stream.
+pub struct TopK<T: Ord> {
+    // the root is the worst element kept: the smallest if `TopK` keeps the
+    // largest elements, and the largest otherwise
+    pq: PriorityQueue<T, Compare<T>>,
+    k: usize,
+}
+
+impl<T: Ord> TopK<T> {
+    /// Constructs a `TopK` that keeps the `k` largest elements pushed into
+    /// it.
+    pub fn largest(k: usize) -> TopK<T> {
+        TopK { pq: PriorityQueue::with_comparator(reverse_cmp), k }
+    }
+
+    /// Constructs a `TopK` that keeps the `k` smallest elements pushed into
+    /// it.
+    pub fn smallest(k: usize) -> TopK<T> {
+        TopK { pq: PriorityQueue::with_comparator(T::cmp), k }
+    }
+
+    /// Adds an element, dropping it or the worst element kept if there are
+    /// already `k` of them. An element that's equal to the worst one kept is
+    /// dropped.
+    pub fn push(&mut self, value: T) {
+        if self.pq.size() < self.k {
+            self.pq.insert(value);
+        } else if self.k > 0 {
+            self.pq.push_pop(value);
+        }
+    }
+
+    /// Returns the worst of the elements kept, which is the `k`-th largest
+    /// (or smallest) pushed so far once there are `k` of them, or `None` if
+    /// no elements are kept.
+    pub fn peek(&self) -> Option<&T> {
+        self.pq.peek()
+    }
+
+    /// Returns `k`, the most elements kept.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pq.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.pq.size()
+    }
+
+    /// Returns an iterator over the elements kept, in no particular order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.pq.iter()
+    }
+
+    /// Consumes the `TopK` and returns the elements kept, best first: in
+    /// descending order if it keeps the largest elements, and in ascending
+    /// order otherwise.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.pq.into_sorted_vec()
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a TopK<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopK;
+    use super::super::super::tests::random_permutation;
+
+    #[test]
+    fn top_k_keeps_the_largest() {
+        let mut top = TopK::largest(10);
+        assert!(top.is_empty());
+        assert_eq!(top.peek(), None);
+        top.extend(random_permutation(10000, 1));
+        assert_eq!(top.size(), 10);
+        assert_eq!(top.k(), 10);
+        assert_eq!(top.peek(), Some(&9990));
+        assert_eq!(top.iter().count(), 10);
+        assert_eq!(top.into_sorted_vec(),
+                   (9990..10000).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn top_k_keeps_the_smallest() {
+        let mut top = TopK::smallest(5);
+        top.extend(random_permutation(1000, 2));
+        assert_eq!(top.peek(), Some(&4));
+        assert_eq!(top.into_sorted_vec(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn top_k_of_short_streams() {
+        let mut top = TopK::largest(10);
+        top.extend(vec![3, 1, 2]);
+        assert_eq!(top.size(), 3);
+        assert_eq!(top.into_sorted_vec(), vec![3, 2, 1]);
+
+        let mut top = TopK::largest(0);
+        top.extend(vec![3, 1, 2]);
+        assert!(top.is_empty());
+        assert_eq!(top.into_sorted_vec(), Vec::<isize>::new());
+    }
+
+    #[test]
+    fn top_k_with_duplicates() {
+        let input: Vec<isize> =
+            random_permutation(1000, 3).iter().map(|x| x % 10).collect();
+        let mut top = TopK::largest(150);
+        top.extend(input.iter().cloned());
+        let mut expected = vec![9; 100];
+        expected.extend(vec![8; 50]);
+        assert_eq!(top.into_sorted_vec(), expected);
+
+        let mut top = TopK::smallest(3);
+        top.extend(input);
+        assert_eq!(top.into_sorted_vec(), vec![0, 0, 0]);
+    }
+}
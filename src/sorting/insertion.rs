@@ -1,12 +1,58 @@
 //! Algorithm 2.2: Insertion sort.
 
+use std::cmp::Ordering;
+
+use super::{Ops, Sorter};
+use super::stats::{Recorder, SortStats};
+
+/// Insertion sort is stable, since it only exchanges adjacent elements that
+/// are out of order.
+pub const STABLE: bool = true;
+
 /// Sort `v` using insertion sort.
-pub fn sort<T: Ord>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using insertion sort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using insertion sort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using insertion sort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Insertion sort as a `Sorter`.
+pub struct Insertion;
+
+impl<T: Ord> Sorter<T> for Insertion {
+    fn name(&self) -> &'static str { "insertion" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+// Sort `v`, using `ops` for compares and exchanges.
+pub(super) fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
     for i in 1..n {
         let mut j = i;
-        while j > 0 && v[j] < v[j-1] {
-            v.swap(j, j-1);
+        while j > 0 && ops.less(&v[j], &v[j-1]) {
+            ops.exch(v, j, j-1);
             j -= 1;
         }
     }
@@ -14,11 +60,58 @@ pub fn sort<T: Ord>(v: &mut Vec<T>) {
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::tests::test_sort;
+    use super::{sort, sort_by, sort_by_key, sort_with_stats};
+    use super::super::tests::{random_permutation, stats_for, test_sort,
+                              test_sort_by_key, test_sort_descending,
+                              test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    fn inversions(v: &[isize]) -> usize {
+        let mut count = 0;
+        for i in 0..v.len() {
+            for j in i+1..v.len() {
+                if v[j] < v[i] { count += 1; }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn sort_with_stats_counts_inversions() {
+        let n = 100;
+        let sorted: Vec<isize> = (0..n as isize).collect();
+        let stats = stats_for(sort_with_stats, &sorted);
+        assert_eq!(stats.compares, n-1);
+        assert_eq!(stats.exchanges, 0);
+
+        let reversed: Vec<isize> = sorted.iter().rev().cloned().collect();
+        let stats = stats_for(sort_with_stats, &reversed);
+        assert_eq!(stats.compares, n*(n-1)/2);
+        assert_eq!(stats.exchanges, n*(n-1)/2);
+
+        let input = random_permutation(n, 1);
+        let stats = stats_for(sort_with_stats, &input);
+        assert_eq!(stats.exchanges, inversions(&input));
+        assert!(stats.compares >= stats.exchanges);
+        assert!(stats.compares < stats.exchanges + n);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        test_stability(|v| sort_by_key(v, |r| r.0), true);
+    }
 }
@@ -0,0 +1,169 @@
+//! Introsort: quicksort with a guaranteed O(N log N) worst case.
+//!
+//! Quicksort is fast on average but takes quadratic time on bad inputs, and
+//! its recursion depth can be linear. Introsort (Musser, 1997) bounds the
+//! recursion depth at about 2 lg N; subarrays that reach that depth are sorted
+//! with heapsort instead. Small subarrays are finished with insertion sort.
+
+use std::cmp::Ordering;
+
+use super::{Ops, Sorter};
+use super::heap;
+use super::insertion;
+use super::quick;
+use super::stats::{Recorder, SortStats};
+
+// Subarrays with at most this many elements are sorted with insertion sort.
+const CUTOFF: usize = 16;
+
+/// Introsort is not stable, like the quicksort and heapsort it's built on.
+pub const STABLE: bool = false;
+
+/// Sort `v` using introsort.
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using introsort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using introsort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using introsort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Introsort as a `Sorter`.
+pub struct Intro;
+
+impl<T: Ord> Sorter<T> for Intro {
+    fn name(&self) -> &'static str { "intro" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+pub(super) fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let n = v.len();
+    sort_part(v, 0, n, 2 * floor_lg(n), ops);
+}
+
+// Sort `v[lo..hi]`, switching to heapsort after `depth` more partitioning
+// steps.
+fn sort_part<T, F, R>(v: &mut [T], lo: usize, hi: usize, depth: usize,
+                      ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    if hi <= lo + CUTOFF {
+        insertion::sort_ops(&mut v[lo..hi], ops);
+        return;
+    }
+    if depth == 0 {
+        heap::sort::sort_ops(&mut v[lo..hi], ops);
+        return;
+    }
+    let m = quick::median_of_3(v, lo, lo + (hi - lo)/2, hi-1, ops);
+    ops.exch(v, lo, m);
+    let j = quick::partition(v, lo, hi, ops);
+    sort_part(v, lo, j, depth-1, ops);
+    sort_part(v, j+1, hi, depth-1, ops);
+}
+
+// Largest `k` such that 2^k <= n, or 0 if `n` is 0.
+fn floor_lg(n: usize) -> usize {
+    let mut k = 0;
+    while n >> (k+1) > 0 {
+        k += 1;
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{floor_lg, sort, sort_by, sort_by_key, sort_with_stats, STABLE};
+    use super::super::quick;
+    use super::super::tests::{lg, mcilroy_killer, random_permutation, stats_for,
+                              test_sort, test_sort_by_key, test_sort_descending,
+                              test_sort_no_clone, test_sort_strings,
+                              test_stability};
+
+    #[test]
+    fn floor_lg_works() {
+        assert_eq!(0, floor_lg(0));
+        assert_eq!(0, floor_lg(1));
+        assert_eq!(1, floor_lg(2));
+        assert_eq!(1, floor_lg(3));
+        assert_eq!(10, floor_lg(1024));
+        assert_eq!(10, floor_lg(2047));
+    }
+
+    #[test]
+    fn sort_works() {
+        test_sort(sort);
+        test_sort_strings(sort);
+        test_sort_no_clone(sort);
+    }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_works_for_larger_inputs() {
+        for &n in &[17, 100, 1000] {
+            let mut v = random_permutation(n, n);
+            sort(&mut v);
+            assert_eq!(v, (0..n as isize).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn sorted_input_does_not_overflow_stack() {
+        let n = 100000;
+        let mut v: Vec<isize> = (0..n).collect();
+        sort(&mut v);
+        assert_eq!(v, (0..n).collect::<Vec<_>>());
+        v.reverse();
+        sort(&mut v);
+        assert_eq!(v, (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mcilroy_killer_input() {
+        let n = 2000;
+        let n_lg_n = n as f64 * lg(n);
+
+        // quadratic for plain quicksort ...
+        let input = mcilroy_killer(n, |v, compare| quick::sort_by(v, compare));
+        let stats = stats_for(quick::sort_with_stats, &input);
+        assert!(stats.compares > n*n/4);
+
+        // ... but not for introsort
+        let input = mcilroy_killer(n, |v, compare| sort_by(v, compare));
+        let stats = stats_for(sort_with_stats, &input);
+        assert!((stats.compares as f64) < 4.0 * n_lg_n);
+    }
+
+    #[test]
+    fn stability_matches_marker() {
+        test_stability(|v| sort_by_key(v, |r| r.0), STABLE);
+    }
+}
@@ -1,16 +1,62 @@
 //! Algorithm 2.5 Bottom-up merge sort.
 
-use std::cmp;
+use std::cmp::{self, Ordering};
+
+use super::super::{Ops, Sorter};
+use super::super::stats::{Recorder, SortStats};
+
+/// Bottom-up merge sort is stable, since it merges adjacent subarrays with the
+/// same stable `merge` as the top-down sort.
+pub const STABLE: bool = true;
 
 /// Sort `v` using a bottom-up merge sort.
-pub fn sort<T: Ord + Copy>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using a bottom-up merge sort, with the order defined by
+/// `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using a bottom-up merge sort, comparing the keys extracted by
+/// `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using a bottom-up merge sort, adding the number of operations to
+/// `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Bottom-up merge sort as a `Sorter`.
+pub struct BottomUp;
+
+impl<T: Ord> Sorter<T> for BottomUp {
+    fn name(&self) -> &'static str { "merge_bu" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { false }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
-    let mut aux = v.clone();
+    let mut aux = vec![0; n];
     let mut sz = 1;
     while sz < n {
         let mut lo = 0;
         while lo < n-sz {
-            super::merge(v, &mut aux, lo, lo+sz, cmp::min(lo+sz+sz, n));
+            super::merge(v, &mut aux, lo, lo+sz, cmp::min(lo+sz+sz, n), ops);
             lo += sz+sz;
         }
         sz += sz;
@@ -19,11 +65,53 @@ pub fn sort<T: Ord + Copy>(v: &mut Vec<T>) {
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::super::tests::test_sort;
+    use super::{sort, sort_by, sort_by_key, sort_with_stats};
+    use super::super::super::tests::{lg, random_permutation, stats_for,
+                                     test_sort, test_sort_by_key,
+                                     test_sort_descending, test_sort_no_clone,
+                                     test_sort_strings, test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_works_for_strings() {
+        test_sort_strings(sort);
+    }
+
+    #[test]
+    fn sort_works_without_clone() {
+        test_sort_no_clone(sort);
+    }
+
+    #[test]
+    fn sort_with_stats_counts_compares() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        let n_lg_n = n as f64 * lg(n);
+        assert!((stats.compares as f64) >= 0.5 * n_lg_n);
+        assert!((stats.compares as f64) <= n_lg_n);
+        // each level of merging takes at most N compares and N exchanges,
+        // N writes to fill `aux` with indices and 2N reads and N writes of
+        // `aux` to follow them: 10N accesses, instead of the 6N of a merge
+        // that copies the elements
+        assert!((stats.array_accesses() as f64) <= 10.0 * n_lg_n);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        test_stability(|v| sort_by_key(v, |r| r.0), true);
+    }
 }
@@ -1,37 +1,105 @@
 //! Implementations of merge sort.
 
 pub mod bottom_up;
+pub mod natural;
 pub mod top_down;
 
-// Merge v[lo..mid] with v[mid..hi], using `aux` for temporary storage.
-fn merge<T: Ord + Copy>(v: &mut Vec<T>, aux: &mut Vec<T>,
-                        lo:usize, mid:usize, hi:usize) {
-    // copy values to `aux`
-    for k in lo..hi {
-        aux[k] = v[k];
-    }
+use std::cmp::Ordering;
 
-    // merge back to `v`
+use super::Ops;
+use super::stats::Recorder;
+
+// Merge v[lo..mid] with v[mid..hi], using `aux` for temporary storage.
+//
+// Elements are moved only by swapping, so `T` needn't be `Copy` or `Clone`:
+// first `aux[lo..hi]` is filled with the index of the element that belongs at
+// each position, then the elements are moved there by following the cycles of
+// that permutation. Each access to `aux` is recorded as an array read or
+// write.
+fn merge<T, F, R>(v: &mut [T], aux: &mut [usize],
+                  lo:usize, mid:usize, hi:usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    // merge indices into `aux`
     let mut i = lo;
     let mut j = mid;
-    for k in lo..hi {
-        if      i >= mid        { v[k] = aux[j]; j += 1; }
-        else if j >= hi         { v[k] = aux[i]; i += 1; }
-        else if aux[j] < aux[i] { v[k] = aux[j]; j += 1; }
-        else                    { v[k] = aux[i]; i += 1; }
+    for slot in &mut aux[lo..hi] {
+        let take_right = if      i >= mid { true }
+                         else if j >= hi  { false }
+                         else { ops.less(&v[j], &v[i]) };
+        ops.write();
+        if take_right { *slot = j; j += 1; }
+        else          { *slot = i; i += 1; }
+    }
+    permute(v, aux, lo, hi, ops);
+}
+
+// Move the element at index `aux[k]` to position `k`, for each `k` in
+// `lo..hi`, marking each position as done by setting `aux[k] = k`.
+fn permute<T, F, R>(v: &mut [T], aux: &mut [usize],
+                    lo: usize, hi: usize, ops: &mut Ops<F, R>)
+    where R: Recorder {
+    for start in lo..hi {
+        let mut k = start;
+        loop {
+            ops.read();
+            let from = aux[k];
+            if from == k { break; }
+            ops.write();
+            aux[k] = k;
+            if from == start { break; }
+            ops.exch(v, k, from);
+            k = from;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::merge;
+    use super::super::Ops;
+    use super::super::stats::SortStats;
 
     #[test]
     fn merge_works() {
-        //                                                 0 1 2 3 4 5 6 7
-        let mut aux = vec![0; 8]; let mut v =         vec![2,5,7,4,1,3,8,9];
-        merge(&mut v, &mut aux, 2,3,4); assert_eq!(v, vec![2,5,4,7,1,3,8,9]);
-        merge(&mut v, &mut aux, 0,2,4); assert_eq!(v, vec![2,4,5,7,1,3,8,9]);
-        merge(&mut v, &mut aux, 0,4,8); assert_eq!(v, vec![1,2,3,4,5,7,8,9]);
+        let mut rec = ();
+        let c = &mut Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+        //                                                    0 1 2 3 4 5 6 7
+        let mut aux = vec![0; 8]; let mut v =            vec![2,5,7,4,1,3,8,9];
+        merge(&mut v, &mut aux, 2,3,4, c); assert_eq!(v, vec![2,5,4,7,1,3,8,9]);
+        merge(&mut v, &mut aux, 0,2,4, c); assert_eq!(v, vec![2,4,5,7,1,3,8,9]);
+        merge(&mut v, &mut aux, 0,4,8, c); assert_eq!(v, vec![1,2,3,4,5,7,8,9]);
+    }
+
+    #[test]
+    fn merge_counts_array_accesses() {
+        let mut stats = SortStats::new();
+        let mut v = vec![2, 5, 1, 3];
+        let mut aux = vec![0; 4];
+        merge(&mut v, &mut aux, 0, 2, 4,
+              &mut Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut stats));
+        assert_eq!(v, vec![1, 2, 3, 5]);
+        // 3 compares fill aux with [2, 0, 3, 1], writing it 4 times; the
+        // cycle 0 <- 2 <- 3 <- 1 takes 3 exchanges and 4 reads and writes of
+        // aux, and positions 1, 2 and 3 are then read once to find them done
+        assert_eq!(stats, SortStats {
+            compares: 3,
+            exchanges: 3,
+            reads: 2*3 + 2*3 + 4 + 3,
+            writes: 2*3 + 4 + 4,
+        });
+    }
+
+    #[test]
+    fn merge_follows_long_cycles() {
+        let mut rec = ();
+        let c = &mut Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+        let mut aux = vec![0; 12];
+        let mut v = vec![1,3,5,7,9,11,0,2,4,6,8,10];
+        merge(&mut v, &mut aux, 0,6,12, c);
+        assert_eq!(v, (0..12).collect::<Vec<_>>());
+
+        let mut v = vec![6,7,8,9,0,1,2,3,4,5];
+        merge(&mut v, &mut aux, 0,4,10, c);
+        assert_eq!(v, (0..10).collect::<Vec<_>>());
     }
 }
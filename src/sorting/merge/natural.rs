@@ -0,0 +1,411 @@
+//! Natural merge sort (Exercise 2.2.16), in the style of TimSort.
+//!
+//! Instead of merging fixed-size subarrays, the sort finds the runs that are
+//! already in order: ascending runs are used as they are and strictly
+//! descending ones are reversed. Runs shorter than a minimum length are
+//! extended with binary insertion sort, and the runs are kept on a stack whose
+//! lengths grow at least as fast as the Fibonacci numbers, which keeps merges
+//! balanced. When one run keeps winning during a merge, the merge switches to
+//! galloping: it finds how many elements to take from that run with an
+//! exponential search instead of one compare per element.
+//!
+//! The sort is stable. On input that's already sorted it makes N - 1
+//! compares, and in general it makes at most about N lg N.
+
+use std::cmp::{self, Ordering};
+
+use super::super::{Ops, Sorter};
+use super::super::stats::{Recorder, SortStats};
+
+// Inputs shorter than this are sorted with binary insertion sort alone, and
+// the minimum run length is between `MIN_MERGE/2` and `MIN_MERGE`.
+const MIN_MERGE: usize = 32;
+
+// Initial number of consecutive wins that switches a merge into galloping.
+const MIN_GALLOP: usize = 7;
+
+/// The natural merge sort is stable.
+pub const STABLE: bool = true;
+
+/// Sort `v` using a natural merge sort.
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using a natural merge sort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using a natural merge sort, comparing the keys extracted by
+/// `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using a natural merge sort, adding the number of operations to
+/// `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Natural merge sort as a `Sorter`.
+pub struct Natural;
+
+impl<T: Ord> Sorter<T> for Natural {
+    fn name(&self) -> &'static str { "merge_natural" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { false }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let n = v.len();
+    if n < 2 { return; }
+    if n < MIN_MERGE {
+        let run = count_run(v, 0, n, ops);
+        binary_insertion_sort(v, 0, n, run, ops);
+        return;
+    }
+
+    let mut merger = Merger {
+        aux: vec![0; n],
+        runs: vec![],
+        min_gallop: MIN_GALLOP,
+    };
+    let min_run = min_run_length(n);
+    let mut lo = 0;
+    while lo < n {
+        let mut len = count_run(v, lo, n, ops);
+        if len < min_run {
+            let forced = cmp::min(min_run, n - lo);
+            binary_insertion_sort(v, lo, lo + forced, lo + len, ops);
+            len = forced;
+        }
+        merger.runs.push(Run { start: lo, len });
+        merger.merge_collapse(v, ops);
+        lo += len;
+    }
+    merger.merge_force_collapse(v, ops);
+}
+
+// The minimum run length for an input of length `n`: `n` itself if it's
+// small, otherwise a length close to `MIN_MERGE/2` such that `n/min_run` is a
+// power of two or slightly less.
+fn min_run_length(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+// Return the length of the run that starts at `v[lo]` and ends by `v[hi-1]`.
+// A descending run is reversed, so the run is ascending afterwards. Only
+// strictly descending runs are reversed, which keeps the sort stable.
+fn count_run<T, F, R>(v: &mut [T], lo: usize, hi: usize, ops: &mut Ops<F, R>)
+    -> usize
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let mut end = lo + 1;
+    if end == hi { return 1; }
+    if ops.less(&v[end], &v[lo]) {
+        end += 1;
+        while end < hi && ops.less(&v[end], &v[end-1]) {
+            end += 1;
+        }
+        let (mut i, mut j) = (lo, end - 1);
+        while i < j {
+            ops.exch(v, i, j);
+            i += 1;
+            j -= 1;
+        }
+    } else {
+        end += 1;
+        while end < hi && !ops.less(&v[end], &v[end-1]) {
+            end += 1;
+        }
+    }
+    end - lo
+}
+
+// Sort `v[lo..hi]`, given that `v[lo..start]` is already sorted. Each element
+// is inserted after any equal elements to its left.
+fn binary_insertion_sort<T, F, R>(v: &mut [T], lo: usize, hi: usize,
+                                  start: usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    for i in start..hi {
+        let (mut left, mut right) = (lo, i);
+        while left < right {
+            let mid = left + (right - left)/2;
+            if ops.less(&v[i], &v[mid]) { right = mid; }
+            else                        { left = mid + 1; }
+        }
+        for k in (left..i).rev() {
+            ops.exch(v, k, k+1);
+        }
+    }
+}
+
+// Return the first index `k` in `lo..hi` for which `pred(k)` is false, given
+// that `pred` is true for some prefix of the range and false after it. Makes
+// about 2 lg d calls to `pred`, where `d` is the distance from `lo` to `k`.
+fn gallop<P>(lo: usize, hi: usize, mut pred: P) -> usize
+    where P: FnMut(usize) -> bool {
+    // exponential search for a range `lo+last..lo+ofs` that contains `k`
+    let (mut last, mut ofs) = (0, 1);
+    while lo + ofs <= hi && pred(lo + ofs - 1) {
+        last = ofs;
+        ofs = 2*ofs + 1;
+    }
+    let (mut left, mut right) = (lo + last, cmp::min(lo + ofs, hi));
+
+    // binary search
+    while left < right {
+        let mid = left + (right - left)/2;
+        if pred(mid) { left = mid + 1; }
+        else         { right = mid; }
+    }
+    left
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+// The state of the merges: the pending runs, the index array used by `merge`,
+// and the current galloping threshold.
+struct Merger {
+    aux: Vec<usize>,
+    runs: Vec<Run>,
+    min_gallop: usize,
+}
+
+impl Merger {
+    // Merge runs until the lengths on the stack satisfy
+    //
+    //     len[i-2] > len[i-1] + len[i]   and   len[i-1] > len[i]
+    //
+    // near the top of the stack.
+    fn merge_collapse<T, F, R>(&mut self, v: &mut [T], ops: &mut Ops<F, R>)
+        where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            let len = |i: usize| self.runs[i].len;
+            if (n > 0 && len(n-1) <= len(n) + len(n+1)) ||
+               (n > 1 && len(n-2) <= len(n-1) + len(n)) {
+                if len(n-1) < len(n+1) { n -= 1; }
+            } else if len(n) > len(n+1) {
+                break;
+            }
+            self.merge_at(v, n, ops);
+        }
+    }
+
+    // Merge all the runs on the stack into one.
+    fn merge_force_collapse<T, F, R>(&mut self, v: &mut [T],
+                                     ops: &mut Ops<F, R>)
+        where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n > 0 && self.runs[n-1].len < self.runs[n+1].len { n -= 1; }
+            self.merge_at(v, n, ops);
+        }
+    }
+
+    // Merge the runs at positions `i` and `i+1` of the stack.
+    fn merge_at<T, F, R>(&mut self, v: &mut [T], i: usize,
+                         ops: &mut Ops<F, R>)
+        where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+        let a = self.runs[i];
+        let b = self.runs.remove(i+1);
+        self.runs[i].len += b.len;
+        self.merge(v, a.start, b.start, b.start + b.len, ops);
+    }
+
+    // Merge v[lo..mid] with v[mid..hi], galloping when one side keeps
+    // winning.
+    fn merge<T, F, R>(&mut self, v: &mut [T],
+                      lo: usize, mid: usize, hi: usize, ops: &mut Ops<F, R>)
+        where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+        // elements of the left run that are <= v[mid] are already in place,
+        // as are elements of the right run that are >= v[mid-1]
+        let lo = gallop(lo, mid, |i| !ops.less(&v[mid], &v[i]));
+        if lo == mid { return; }
+        let hi = gallop(mid, hi, |j| ops.less(&v[j], &v[mid-1]));
+
+        // merge indices into `aux`
+        let aux = &mut self.aux;
+        let (mut i, mut j, mut k) = (lo, mid, lo);
+        let (mut wins_left, mut wins_right) = (0, 0);
+        while i < mid && j < hi {
+            if wins_left >= self.min_gallop || wins_right >= self.min_gallop {
+                let end = gallop(i, mid, |x| !ops.less(&v[j], &v[x]));
+                let n_left = end - i;
+                while i < end { aux[k] = i; i += 1; k += 1; }
+                if i == mid { break; }
+
+                let end = gallop(j, hi, |y| ops.less(&v[y], &v[i]));
+                let n_right = end - j;
+                while j < end { aux[k] = j; j += 1; k += 1; }
+
+                // galloping pays off only if it takes long stretches; adjust
+                // the threshold for switching to it accordingly
+                if n_left < MIN_GALLOP && n_right < MIN_GALLOP {
+                    self.min_gallop += 1;
+                    wins_left = 0;
+                    wins_right = 0;
+                } else if self.min_gallop > 1 {
+                    self.min_gallop -= 1;
+                }
+            } else if ops.less(&v[j], &v[i]) {
+                aux[k] = j; j += 1; k += 1;
+                wins_right += 1;
+                wins_left = 0;
+            } else {
+                aux[k] = i; i += 1; k += 1;
+                wins_left += 1;
+                wins_right = 0;
+            }
+        }
+        while i < mid { aux[k] = i; i += 1; k += 1; }
+        while j < hi  { aux[k] = j; j += 1; k += 1; }
+        // each of `aux[lo..hi]` was written once
+        for _ in lo..hi {
+            ops.write();
+        }
+
+        super::permute(v, aux, lo, hi, ops);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gallop, min_run_length, sort, sort_by, sort_by_key,
+                sort_with_stats, MIN_MERGE};
+    use super::super::super::tests::{lg, random_permutation, stats_for,
+                                     test_sort, test_sort_by_key,
+                                     test_sort_descending, test_sort_no_clone,
+                                     test_sort_strings, test_stability};
+
+    #[test]
+    fn sort_works() {
+        test_sort(sort);
+        test_sort_strings(sort);
+        test_sort_no_clone(sort);
+    }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_works_for_larger_inputs() {
+        for &n in &[31, 32, 33, 100, 1000, 10000] {
+            let mut v = random_permutation(n, n);
+            sort(&mut v);
+            assert_eq!(v, (0..n as isize).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn sort_is_stable_with_many_duplicates() {
+        let n = 5000;
+        let input: Vec<(isize, usize)> = random_permutation(n, 3).iter()
+            .enumerate().map(|(i, &x)| (x % 10, i)).collect();
+        let mut v = input.clone();
+        sort_by_key(&mut v, |r| r.0);
+        let mut expected = input.clone();
+        expected.sort_by_key(|r| r.0);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn min_run_length_works() {
+        assert_eq!(min_run_length(10), 10);
+        assert_eq!(min_run_length(1024), 16);
+        assert_eq!(min_run_length(1025), 17);
+        for n in MIN_MERGE..5000 {
+            let m = min_run_length(n);
+            assert!((MIN_MERGE/2..=MIN_MERGE).contains(&m));
+        }
+    }
+
+    #[test]
+    fn gallop_works() {
+        let v = [1, 3, 3, 5, 8, 13, 21, 34];
+        for x in 0..40 {
+            let expected = v.iter().filter(|&&y| y < x).count();
+            assert_eq!(gallop(0, v.len(), |i| v[i] < x), expected);
+        }
+        assert_eq!(gallop(2, 6, |i| v[i] < 6), 4);
+        assert_eq!(gallop(3, 3, |_| true), 3);
+    }
+
+    #[test]
+    fn sorted_input_takes_linear_compares() {
+        let n = 10000;
+        let sorted: Vec<isize> = (0..n as isize).collect();
+        let stats = stats_for(sort_with_stats, &sorted);
+        assert_eq!(stats.compares, n-1);
+        assert_eq!(stats.exchanges, 0);
+
+        let reversed: Vec<isize> = sorted.iter().rev().cloned().collect();
+        let stats = stats_for(sort_with_stats, &reversed);
+        assert_eq!(stats.compares, n-1);
+        assert_eq!(stats.exchanges, n/2);
+    }
+
+    #[test]
+    fn galloping_merges_in_sublinear_compares() {
+        // two long runs, the second of which belongs before the first
+        let n = 10000;
+        let mut input: Vec<isize> = (n as isize/2..n as isize).collect();
+        input.extend(0..n as isize/2);
+        let stats = stats_for(sort_with_stats, &input);
+        assert!(stats.compares < n + 100);
+
+        // two runs whose blocks of 100 elements interleave
+        let blocks = |first: isize| (0..n as isize/2)
+            .map(move |i| (i/100)*200 + first + i%100);
+        let input: Vec<isize> = blocks(0).chain(blocks(100)).collect();
+        let stats = stats_for(sort_with_stats, &input);
+        assert!(stats.compares < 2*n);
+    }
+
+    #[test]
+    fn sort_with_stats_counts_compares() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        let n_lg_n = n as f64 * lg(n);
+        assert!((stats.compares as f64) <= n_lg_n);
+
+        // a sorted input with a few elements out of place
+        let mut input: Vec<isize> = (0..n as isize).collect();
+        for i in 0..5 {
+            input.swap(i*150, i*150 + 100);
+        }
+        let stats = stats_for(sort_with_stats, &input);
+        assert!((stats.compares as f64) < 0.3 * n_lg_n);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        test_stability(|v| sort_by_key(v, |r| r.0), true);
+    }
+}
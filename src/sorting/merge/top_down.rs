@@ -1,31 +1,226 @@
 //! Algorithm 2.4 Top-down merge sort.
 
+use std::cmp::Ordering;
+use std::thread;
+
+use super::super::{check_threads, split_threads, Ops, Sorter};
+use super::super::stats::{Recorder, SortStats};
+
+/// Top-down merge sort is stable, since `merge` takes from the left subarray
+/// when the keys are equal.
+pub const STABLE: bool = true;
+
 /// Sort `v` using a top-down merge sort.
-pub fn sort<T: Ord + Copy>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using a top-down merge sort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using a top-down merge sort, comparing the keys extracted by
+/// `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using a top-down merge sort, adding the number of operations to
+/// `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+// Subarrays with at most this many elements are sorted by a single thread in
+// `par_sort`.
+const PAR_CUTOFF: usize = 1 << 13;
+
+/// Sort `v` using a top-down merge sort on `threads` threads. The halves of
+/// the slice are sorted in parallel, and so on recursively until each thread
+/// has a subarray of its own or the subarrays are small; the merges above
+/// that are done by one thread each. The sort is stable.
+///
+/// # Panics
+///
+/// Panics if `threads` is 0.
+pub fn par_sort<T: Ord + Send>(v: &mut [T], threads: usize) {
+    par_sort_by(v, threads, |a, b| a.cmp(b));
+}
+
+/// Like `par_sort`, with the order defined by `compare`.
+pub fn par_sort_by<T, F>(v: &mut [T], threads: usize, compare: F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    check_threads(threads);
+    let mut aux = vec![0; v.len()];
+    par_sort_part(v, &mut aux, threads, PAR_CUTOFF, &compare);
+}
+
+/// Like `par_sort`, comparing the keys extracted by `key`.
+pub fn par_sort_by_key<T, K, F>(v: &mut [T], threads: usize, key: F)
+    where T: Send, K: Ord, F: Fn(&T) -> K + Sync {
+    par_sort_by(v, threads, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Top-down merge sort as a `Sorter`.
+pub struct TopDown;
+
+impl<T: Ord> Sorter<T> for TopDown {
+    fn name(&self) -> &'static str { "merge_td" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { false }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
-    let mut aux = v.clone();
-    sort_part(v, &mut aux, 0, n);
+    let mut aux = vec![0; n];
+    sort_part(v, &mut aux, 0, n, ops);
 }
 
 // Sort `v[lo..hi]`, using `aux` for temporary storage.
-fn sort_part<T: Ord + Copy>(v: &mut Vec<T>, aux: &mut Vec<T>,
-                            lo: usize, hi: usize) {
+fn sort_part<T, F, R>(v: &mut [T], aux: &mut [usize],
+                      lo: usize, hi: usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     if hi < lo+2 {
         return;
     }
     let mid = lo + (hi - lo)/2;
-    sort_part(v, aux, lo, mid);
-    sort_part(v, aux, mid, hi);
-    super::merge(v, aux, lo, mid, hi);
+    sort_part(v, aux, lo, mid, ops);
+    sort_part(v, aux, mid, hi, ops);
+    super::merge(v, aux, lo, mid, hi, ops);
+}
+
+// Sort `v` on `threads` threads, sorting subarrays of at most `cutoff`
+// elements sequentially.
+fn par_sort_part<T, F>(v: &mut [T], aux: &mut [usize], threads: usize,
+                       cutoff: usize, compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    let n = v.len();
+    if threads == 1 || n <= cutoff {
+        sort_part(v, aux, 0, n, &mut Ops::new(compare, &mut ()));
+        return;
+    }
+    let mid = n/2;
+    let left_threads = split_threads(threads, mid, n);
+    {
+        let (left, right) = v.split_at_mut(mid);
+        let (left_aux, right_aux) = aux.split_at_mut(mid);
+        thread::scope(|s| {
+            s.spawn(|| {
+                par_sort_part(left, left_aux, left_threads, cutoff, compare);
+            });
+            par_sort_part(right, right_aux, threads - left_threads, cutoff,
+                          compare);
+        });
+    }
+    super::merge(v, aux, 0, mid, n, &mut Ops::new(compare, &mut ()));
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::super::tests::test_sort;
+    use super::{par_sort, par_sort_by, par_sort_by_key, par_sort_part, sort,
+                sort_by, sort_by_key, sort_with_stats};
+    use super::super::super::tests::{lg, random_permutation, stats_for,
+                                     test_sort, test_sort_by_key,
+                                     test_sort_descending, test_sort_no_clone,
+                                     test_sort_strings, test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_works_for_strings() {
+        test_sort_strings(sort);
+    }
+
+    #[test]
+    fn sort_works_without_clone() {
+        test_sort_no_clone(sort);
+    }
+
+    #[test]
+    fn sort_with_stats_counts_compares() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        let n_lg_n = n as f64 * lg(n);
+        assert!((stats.compares as f64) >= 0.5 * n_lg_n);
+        assert!((stats.compares as f64) <= n_lg_n);
+        // each level of merging takes at most N compares and N exchanges,
+        // N writes to fill `aux` with indices and 2N reads and N writes of
+        // `aux` to follow them: 10N accesses, instead of the 6N of a merge
+        // that copies the elements
+        assert!((stats.array_accesses() as f64) <= 10.0 * n_lg_n);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        test_stability(|v| sort_by_key(v, |r| r.0), true);
+    }
+
+    #[test]
+    fn par_sort_works() {
+        test_sort(|v| par_sort(v, 4));
+        test_sort_descending(|v| par_sort_by(v, 2, |a, b| b.cmp(a)));
+        test_sort_by_key(|v| par_sort_by_key(v, 3, |r| r.0));
+        test_sort_strings(|v| par_sort(v, 2));
+        test_sort_no_clone(|v| par_sort(v, 2));
+        for &threads in &[1, 2, 3, 4, 7] {
+            let mut v = random_permutation(100000, threads);
+            par_sort(&mut v, threads);
+            assert_eq!(v, (0..100000).collect::<Vec<_>>());
+        }
+    }
+
+    // Sort with parallel splits down to small subarrays.
+    fn par_sort_small<T: Ord + Send>(v: &mut [T], threads: usize) {
+        let mut aux = vec![0; v.len()];
+        par_sort_part(v, &mut aux, threads, 4, &|a: &T, b: &T| a.cmp(b));
+    }
+
+    #[test]
+    fn par_sort_splits_small_inputs() {
+        for &threads in &[2, 3, 5, 16, 64] {
+            test_sort(|v| par_sort_small(v, threads));
+            test_sort_no_clone(|v| par_sort_small(v, threads));
+        }
+    }
+
+    #[test]
+    fn par_sort_is_stable() {
+        for &threads in &[2, 3, 8] {
+            test_stability(|v| {
+                let mut aux = vec![0; v.len()];
+                par_sort_part(v, &mut aux, threads, 4,
+                              &|a: &(isize, usize), b: &(isize, usize)| {
+                                  a.0.cmp(&b.0)
+                              });
+            }, true);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn par_sort_needs_a_thread() {
+        par_sort(&mut [2, 1], 0);
+    }
 }
@@ -1,28 +1,185 @@
 //! Implementations of data structures and algorithms from Chapter 2: Sorting.
+//!
+//! Every algorithm is available in three forms, following the slice methods in
+//! the standard library: `sort` sorts a slice of `Ord` values, `sort_by` sorts
+//! with a comparison function, and `sort_by_key` sorts by a key extracted from
+//! each element. The `sort_with_stats` functions count the operations
+//! performed; see the `stats` module.
+//!
+//! Each algorithm's module also has a `STABLE` constant that tells whether the
+//! sort is stable, that is, whether elements that compare equal keep their
+//! original relative order.
+//!
+//! To choose an algorithm at run time, use the `Sorter` trait: `sorters`
+//! returns every algorithm, and `sorter` looks one up by name.
+//!
+//! Top-down merge sort and quicksort also have `par_sort` functions that
+//! split the work between a number of threads; `available_threads` tells how
+//! many the machine can run in parallel.
 
+pub mod external;
 pub mod heap;
 pub mod insertion;
+pub mod intro;
 pub mod merge;
 pub mod quick;
+pub mod select;
 pub mod selection;
 pub mod shell;
+pub mod stats;
+
+use std::cmp::{self, Ordering};
+use std::error;
+use std::fmt;
+use std::thread;
+
+use self::stats::Recorder;
+
+/// A sorting algorithm that can be chosen at run time.
+pub trait Sorter<T: Ord> {
+    /// The name of the algorithm, e.g. `"merge_td"`.
+    fn name(&self) -> &'static str;
+
+    /// True if the sort is stable; the same as the module's `STABLE`.
+    fn is_stable(&self) -> bool;
+
+    /// True if the sort doesn't need extra memory proportional to the length
+    /// of the slice.
+    fn is_in_place(&self) -> bool;
+
+    /// Sort `v`.
+    fn sort(&self, v: &mut [T]);
+
+    /// Sort `v`, with the order defined by `compare`.
+    fn sort_by(&self, v: &mut [T], compare: &mut dyn FnMut(&T, &T) -> Ordering);
+}
+
+/// Return every sorting algorithm.
+pub fn sorters<T: Ord>() -> Vec<Box<dyn Sorter<T>>> {
+    vec![
+        Box::new(selection::Selection),
+        Box::new(insertion::Insertion),
+        Box::new(shell::Shell),
+        Box::new(merge::top_down::TopDown),
+        Box::new(merge::bottom_up::BottomUp),
+        Box::new(merge::natural::Natural),
+        Box::new(quick::Quick),
+        Box::new(quick::Quick3way),
+        Box::new(intro::Intro),
+        Box::new(heap::sort::Heap),
+    ]
+}
+
+/// Return the sorting algorithm called `name`.
+pub fn sorter<T: Ord>(name: &str) -> Result<Box<dyn Sorter<T>>, UnknownSorter> {
+    sorters().into_iter().find(|s| s.name() == name).ok_or_else(|| {
+        UnknownSorter { name: name.to_string() }
+    })
+}
+
+/// The error returned by `sorter` for a name that isn't an algorithm's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownSorter {
+    /// The name that was looked up.
+    pub name: String,
+}
+
+impl fmt::Display for UnknownSorter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> =
+            sorters::<u8>().iter().map(|s| s.name()).collect();
+        write!(f, "unknown sorting algorithm {}; expected one of: {}",
+               self.name, names.join(", "))
+    }
+}
+
+impl error::Error for UnknownSorter {}
 
 /// True if `v` is sorted in ascending order.
-pub fn is_sorted<T: Ord>(v: &Vec<T>) -> bool {
+pub fn is_sorted<T: Ord>(v: &[T]) -> bool {
+    is_sorted_by(v, |a, b| a.cmp(b))
+}
+
+/// True if `v` is sorted in ascending order according to `compare`.
+pub fn is_sorted_by<T, F>(v: &[T], mut compare: F) -> bool
+    where F: FnMut(&T, &T) -> Ordering {
     for win in v.windows(2) {
-        if win[1] < win[0] {
+        if compare(&win[1], &win[0]) == Ordering::Less {
             return false
         }
     }
-    return true;
+    true
+}
+
+/// Return the number of threads the machine can run in parallel, or 1 if
+/// that's unknown.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// Panic unless `threads` is a valid number of threads for a parallel sort.
+fn check_threads(threads: usize) {
+    assert!(threads > 0, "a parallel sort needs at least 1 thread");
+}
+
+// Return how many of `threads` threads should sort the first `left` of `n`
+// elements when a parallel sort splits its work in two, so that each thread
+// gets about the same number of elements and both parts get at least one
+// thread. The parallel sorts only split with 2 or more threads and more
+// elements than their cutoff, so `threads >= 2` and `n > 0` here; with 1
+// thread or a shorter slice they sort sequentially without calling this.
+fn split_threads(threads: usize, left: usize, n: usize) -> usize {
+    let t = (threads * left + n/2) / n;
+    cmp::max(1, cmp::min(t, threads - 1))
+}
+
+// The comparison function used by a sort, together with a recorder that's
+// notified of each operation. The algorithms use `cmp` and `exch` instead of
+// calling the comparison function and `swap` directly.
+struct Ops<'a, F, R: 'a> {
+    compare: F,
+    recorder: &'a mut R,
+}
+
+impl<'a, F, R: Recorder> Ops<'a, F, R> {
+    fn new(compare: F, recorder: &'a mut R) -> Ops<'a, F, R> {
+        Ops { compare, recorder }
+    }
+
+    fn cmp<T>(&mut self, a: &T, b: &T) -> Ordering
+        where F: FnMut(&T, &T) -> Ordering {
+        self.recorder.compare();
+        (self.compare)(a, b)
+    }
+
+    fn less<T>(&mut self, a: &T, b: &T) -> bool
+        where F: FnMut(&T, &T) -> Ordering {
+        self.cmp(a, b) == Ordering::Less
+    }
+
+    fn exch<T>(&mut self, v: &mut [T], i: usize, j: usize) {
+        self.recorder.exchange();
+        v.swap(i, j);
+    }
+
+    fn read(&mut self) {
+        self.recorder.read();
+    }
+
+    fn write(&mut self) {
+        self.recorder.write();
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::is_sorted;
+    use std::cmp::Ordering;
+    use rand::{Rng, SeedableRng, StdRng};
+    use super::{is_sorted, is_sorted_by, sorter, sorters, UnknownSorter};
+    use super::stats::SortStats;
 
     pub fn test_sort<F>(sort: F)
-        where F: Fn(&mut Vec<isize>) -> () {
+        where F: Fn(&mut [isize]) {
         let test_data = vec![
             (vec![],              vec![]),
             (vec![42],            vec![42]),
@@ -35,10 +192,192 @@ mod tests {
             sort(&mut input);
             assert_eq!(input, pair.0);
         }
+
+        let mut array = [9, 4, 1, 2, 5, 3, 0];
+        sort(&mut array[1..6]);
+        assert_eq!(array, [9, 1, 2, 3, 4, 5, 0]);
+
+        // larger inputs, with and without duplicates
+        for &(n, keys) in &[(50, 50), (50, 5), (1000, 1000), (1000, 10)] {
+            let mut v: Vec<isize> = random_permutation(n, n + keys).iter()
+                .map(|x| x % keys as isize).collect();
+            let mut expected = v.clone();
+            expected.sort();
+            sort(&mut v);
+            assert_eq!(v, expected);
+        }
+    }
+
+    /// Test a sort that's expected to put values in descending order.
+    pub fn test_sort_descending<F>(sort: F)
+        where F: Fn(&mut [isize]) {
+        let mut v = vec![4, 1, 2, 5, 3, 2];
+        sort(&mut v);
+        assert_eq!(v, vec![5, 4, 3, 2, 2, 1]);
+    }
+
+    /// Test a sort that's expected to sort records by their first field.
+    pub fn test_sort_by_key<F>(sort: F)
+        where F: Fn(&mut [(isize, char)]) {
+        let mut v = vec![(3, 'c'), (5, 'e'), (1, 'a'), (4, 'd'), (2, 'b')];
+        sort(&mut v);
+        assert_eq!(v, vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e')]);
+    }
+
+    /// Test a sort on heap-allocated values.
+    pub fn test_sort_strings<F>(sort: F)
+        where F: Fn(&mut [String]) {
+        let words = "it was the best of times it was the worst of times";
+        let mut v: Vec<String> =
+            words.split(' ').map(|w| w.to_string()).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        sort(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    /// A type that can be sorted but not copied or cloned.
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct NoClone(isize);
+
+    /// Test a sort on values that can't be cloned.
+    pub fn test_sort_no_clone<F>(sort: F)
+        where F: Fn(&mut [NoClone]) {
+        let input = vec![5, 3, 9, 1, 3, 0, 7, 2, 8, 6, 4];
+        let mut v: Vec<NoClone> = input.iter().map(|&x| NoClone(x)).collect();
+        sort(&mut v);
+        let output: Vec<isize> = v.iter().map(|x| x.0).collect();
+        assert_eq!(output, vec![0, 1, 2, 3, 3, 4, 5, 6, 7, 8, 9]);
     }
+
+    /// Test a sort that's expected to sort records by their first field, and
+    /// to be stable exactly if `stable` is true. The second field of each
+    /// record is its position in the input, so a stable sort leaves the
+    /// records in lexicographic order. An unstable sort must reorder equal
+    /// keys in at least one of the inputs.
+    pub fn test_stability<F>(sort: F, stable: bool)
+        where F: Fn(&mut [(isize, usize)]) {
+        let mut reordered = false;
+        let sizes = [(3, 2), (10, 3), (100, 5), (1000, 10), (1000, 500)];
+        for &(n, keys) in &sizes {
+            for seed in 0..3 {
+                let input: Vec<(isize, usize)> = random_permutation(n, seed)
+                    .iter().enumerate().map(|(i, &x)| (x % keys, i)).collect();
+                let mut v = input.clone();
+                sort(&mut v);
+                assert!(is_sorted_by(&v, |a, b| a.0.cmp(&b.0)));
+                reordered |= !is_sorted(&v);
+
+                // check that `v` is a permutation of `input`
+                let mut expected = input;
+                expected.sort();
+                v.sort();
+                assert_eq!(v, expected);
+            }
+        }
+        assert_eq!(stable, !reordered);
+    }
+
+    /// A random permutation of `0..n`, the same for each `seed`.
+    pub fn random_permutation(n: usize, seed: usize) -> Vec<isize> {
+        let seed: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut v: Vec<isize> = (0..n as isize).collect();
+        rng.shuffle(&mut v);
+        v
+    }
+
+    /// Sort a copy of `input` and return the operation counts.
+    pub fn stats_for<F>(sort: F, input: &[isize]) -> SortStats
+        where F: Fn(&mut [isize], &mut SortStats) {
+        let mut v = input.to_vec();
+        let mut stats = SortStats::new();
+        sort(&mut v, &mut stats);
+        assert!(is_sorted(&v));
+        stats
+    }
+
+    /// McIlroy's adversary for quicksort ("A Killer Adversary for
+    /// Quicksort", 1999). Runs `sort`, a `sort_by` function, with a
+    /// comparison function that decides the values of the elements as late as
+    /// possible, making the pivots bad. Returns those values: an input that
+    /// makes `sort` perform as badly as the adversary could manage.
+    pub fn mcilroy_killer<S>(n: usize, sort: S) -> Vec<isize>
+        where S: Fn(&mut [usize], &mut dyn FnMut(&usize, &usize) -> Ordering) {
+        // elements that haven't been given a value yet are "gas"
+        let gas = n as isize;
+        let mut val = vec![gas; n];
+        let mut n_solid = 0;
+        let mut candidate = 0;
+        {
+            let mut compare = |&x: &usize, &y: &usize| {
+                if val[x] == gas && val[y] == gas {
+                    let z = if x == candidate { x } else { y };
+                    val[z] = n_solid;
+                    n_solid += 1;
+                }
+                if val[x] == gas {
+                    candidate = x;
+                } else if val[y] == gas {
+                    candidate = y;
+                }
+                val[x].cmp(&val[y])
+            };
+            let mut ptr: Vec<usize> = (0..n).collect();
+            sort(&mut ptr, &mut compare);
+        }
+        val
+    }
+
+    /// Binary logarithm of `n`.
+    pub fn lg(n: usize) -> f64 {
+        (n as f64).log2()
+    }
+
+    #[test]
+    fn sorters_work() {
+        for s in sorters::<isize>() {
+            test_sort(|v| s.sort(v));
+        }
+        for s in sorters::<(isize, usize)>() {
+            test_stability(|v| s.sort_by(v, &mut |a, b| a.0.cmp(&b.0)),
+                           s.is_stable());
+        }
+    }
+
+    #[test]
+    fn sorter_finds_every_algorithm_by_name() {
+        let names: Vec<&str> =
+            sorters::<isize>().iter().map(|s| s.name()).collect();
+        for (i, &name) in names.iter().enumerate() {
+            assert!( ! names[..i].contains(&name));
+            assert_eq!(sorter::<isize>(name).unwrap().name(), name);
+        }
+        assert!(sorter::<isize>("merge_td").unwrap().is_stable());
+        assert!( ! sorter::<isize>("merge_td").unwrap().is_in_place());
+        assert!(sorter::<isize>("heap").unwrap().is_in_place());
+    }
+
+    #[test]
+    fn sorter_lists_names_for_unknown_name() {
+        let e = sorter::<isize>("bogo").err().unwrap();
+        assert_eq!(e, UnknownSorter { name: "bogo".to_string() });
+        let message = e.to_string();
+        assert!(message.starts_with("unknown sorting algorithm bogo;"));
+        for s in sorters::<isize>() {
+            assert!(message.contains(s.name()));
+        }
+    }
+
     #[test]
     fn is_sorted_works() {
         assert!(is_sorted(&vec![-9, -8, -6, -2, 0, 2, 2, 5, 6, 9]));
         assert!( ! is_sorted(&vec![-4, -3, -1, 2, 1, 4, 5, 7, 8, 9]));
     }
+
+    #[test]
+    fn is_sorted_by_works() {
+        assert!(super::is_sorted_by(&[3, 2, 2, 1], |a, b| b.cmp(a)));
+        assert!( ! super::is_sorted_by(&[1, 2], |a, b| b.cmp(a)));
+    }
 }
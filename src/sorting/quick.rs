@@ -1,38 +1,283 @@
-//! Algorithm 2.5 Quickort.
+//! Algorithm 2.5 Quickort, and quicksort with 3-way partitioning.
+
+use std::cmp::Ordering;
+use std::thread;
+
+use rand::{self, Rng};
+
+use super::{check_threads, split_threads, Ops, Sorter};
+use super::insertion;
+use super::stats::{Recorder, SortStats};
+
+/// Neither version of quicksort is stable: partitioning exchanges elements
+/// over long distances.
+pub const STABLE: bool = false;
+
+// Subarrays with at most this many elements are sorted with insertion sort by
+// `sort_3way`.
+const CUTOFF: usize = 10;
+
+// Subarrays with at most this many elements are sorted by a single thread in
+// `par_sort`.
+const PAR_CUTOFF: usize = 1 << 13;
 
 /// Sort `v` using a straightforward implementation of quicksort.
-pub fn sort<T: Ord + Copy>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using quicksort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
     let n = v.len();
-    sort_part(v, 0, n);
+    sort_part(v, 0, n, &mut Ops::new(compare, &mut ()));
 }
 
-fn sort_part<T: Ord + Copy>(v: &mut Vec<T>, lo: usize, hi: usize) {
+/// Sort `v` using quicksort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using quicksort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    let n = v.len();
+    sort_part(v, 0, n, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Quicksort as a `Sorter`.
+pub struct Quick;
+
+impl<T: Ord> Sorter<T> for Quick {
+    fn name(&self) -> &'static str { "quick" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+/// Sort `v` using quicksort on `threads` threads. The slice is shuffled and
+/// partitioned around the median of three elements, and the two sides are
+/// sorted in parallel with the threads divided between them in proportion to
+/// their sizes, and so on recursively until each thread has a subarray of its
+/// own or the subarrays are small. A side that's empty gets no thread. Each thread then sorts its subarray with
+/// `sort_3way`, so inputs with many duplicate keys or in sorted order are
+/// fast too.
+///
+/// # Panics
+///
+/// Panics if `threads` is 0.
+pub fn par_sort<T: Ord + Send>(v: &mut [T], threads: usize) {
+    par_sort_by(v, threads, |a, b| a.cmp(b));
+}
+
+/// Like `par_sort`, with the order defined by `compare`.
+pub fn par_sort_by<T, F>(v: &mut [T], threads: usize, compare: F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    check_threads(threads);
+    if threads > 1 && v.len() > PAR_CUTOFF {
+        // so that no input makes the partitions lopsided
+        let mut rec = ();
+        shuffle(v, &mut Ops::new(&compare, &mut rec));
+    }
+    par_sort_part(v, threads, PAR_CUTOFF, &compare);
+}
+
+/// Like `par_sort`, comparing the keys extracted by `key`.
+pub fn par_sort_by_key<T, K, F>(v: &mut [T], threads: usize, key: F)
+    where T: Send, K: Ord, F: Fn(&T) -> K + Sync {
+    par_sort_by(v, threads, |a, b| key(a).cmp(&key(b)));
+}
+
+// Sort `v` on `threads` threads, sorting subarrays of at most `cutoff`
+// elements sequentially.
+fn par_sort_part<T, F>(v: &mut [T], threads: usize, cutoff: usize,
+                       compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    let n = v.len();
+    let mut rec = ();
+    let ops = &mut Ops::new(compare, &mut rec);
+    if threads == 1 || n <= cutoff || n < 3 {
+        sort_3way_ops(v, ops);
+        return;
+    }
+    let m = median_of_3(v, 0, n/2, n-1, ops);
+    ops.exch(v, 0, m);
+    let j = partition(v, 0, n, ops);
+    let (left, right) = v.split_at_mut(j);
+    let right = &mut right[1..];
+    if left.is_empty() || right.is_empty() {
+        let side = if left.is_empty() { right } else { left };
+        par_sort_part(side, threads, cutoff, compare);
+        return;
+    }
+    let left_threads = split_threads(threads, j, n);
+    thread::scope(|s| {
+        s.spawn(|| par_sort_part(left, left_threads, cutoff, compare));
+        par_sort_part(right, threads - left_threads, cutoff, compare);
+    });
+}
+
+fn sort_part<T, F, R>(v: &mut [T], lo: usize, hi: usize, ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     if hi <= lo+1 { return; }
-    let j = partition(v, lo, hi);
-    sort_part(v, lo, j);
-    sort_part(v, j+1, hi);
+    let j = partition(v, lo, hi, ops);
+    sort_part(v, lo, j, ops);
+    sort_part(v, j+1, hi, ops);
 }
 
-fn partition<T: Ord + Copy>(v: &mut Vec<T>, lo: usize, hi: usize) -> usize {
+pub(super) fn partition<T, F, R>(v: &mut [T], lo: usize, hi: usize,
+                                 ops: &mut Ops<F, R>) -> usize
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    // the pivot stays at `v[lo]` until the end
     let mut i = lo;
     let mut j = hi;
-    let x = v[lo];
     loop {
-        loop { i += 1; if v[i] >= x || i == hi-1 { break; } }
-        loop { j -= 1; if x >= v[j] || j == lo   { break; } }
+        loop {
+            i += 1;
+            if !ops.less(&v[i], &v[lo]) || i == hi-1 { break; }
+        }
+        loop {
+            j -= 1;
+            if !ops.less(&v[lo], &v[j]) || j == lo { break; }
+        }
         if i >= j { break; }
-        v.swap(i, j);
+        ops.exch(v, i, j);
     }
-    v.swap(lo, j);
+    ops.exch(v, lo, j);
     return j;
 }
 
+/// Sort `v` using quicksort with 3-way partitioning (Dijkstra's solution to
+/// the Dutch national flag problem). Elements equal to the pivot are excluded
+/// from the recursive calls, so inputs with many duplicate keys take linear
+/// time. The input is shuffled first, pivots are chosen as the median of
+/// three elements, and small subarrays are sorted with insertion sort.
+pub fn sort_3way<T: Ord>(v: &mut [T]) {
+    sort_3way_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using quicksort with 3-way partitioning, with the order defined
+/// by `compare`.
+pub fn sort_3way_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_3way_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using quicksort with 3-way partitioning, comparing the keys
+/// extracted by `key`.
+pub fn sort_3way_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_3way_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using quicksort with 3-way partitioning, adding the number of
+/// operations to `stats`.
+pub fn sort_3way_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_3way_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Quicksort with 3-way partitioning as a `Sorter`.
+pub struct Quick3way;
+
+impl<T: Ord> Sorter<T> for Quick3way {
+    fn name(&self) -> &'static str { "quick3way" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort_3way(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_3way_by(v, compare);
+    }
+}
+
+fn sort_3way_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    shuffle(v, ops);
+    let n = v.len();
+    sort_3way_part(v, 0, n, ops);
+}
+
+// Knuth shuffle.
+fn shuffle<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where R: Recorder {
+    let mut rng = rand::thread_rng();
+    let n = v.len();
+    for i in 0..n {
+        let r = rng.gen_range(i, n);
+        ops.exch(v, i, r);
+    }
+}
+
+fn sort_3way_part<T, F, R>(v: &mut [T], lo: usize, hi: usize,
+                           ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    if hi <= lo + CUTOFF {
+        insertion::sort_ops(&mut v[lo..hi], ops);
+        return;
+    }
+    let (lt, gt) = partition_3way(v, lo, hi, ops);
+    sort_3way_part(v, lo, lt, ops);
+    sort_3way_part(v, gt, hi, ops);
+}
+
+// Partition `v[lo..hi]` into elements less than, equal to and greater than a
+// pivot. Returns `(lt, gt)` such that `v[lo..lt]` < pivot, `v[lt..gt]` ==
+// pivot and `v[gt..hi]` > pivot.
+fn partition_3way<T, F, R>(v: &mut [T], lo: usize, hi: usize,
+                           ops: &mut Ops<F, R>) -> (usize, usize)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let m = median_of_3(v, lo, lo + (hi - lo)/2, hi-1, ops);
+    ops.exch(v, lo, m);
+
+    // `v[lt]` is always equal to the pivot
+    let mut lt = lo;
+    let mut i = lo+1;
+    let mut gt = hi;
+    while i < gt {
+        match ops.cmp(&v[i], &v[lt]) {
+            Ordering::Less    => { ops.exch(v, lt, i); lt += 1; i += 1; },
+            Ordering::Greater => { gt -= 1; ops.exch(v, i, gt); },
+            Ordering::Equal   => { i += 1; },
+        }
+    }
+    (lt, gt)
+}
+
+// Return the index of the median of `v[i]`, `v[j]` and `v[k]`.
+pub(super) fn median_of_3<T, F, R>(v: &[T], i: usize, j: usize, k: usize,
+                                   ops: &mut Ops<F, R>) -> usize
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    if ops.less(&v[i], &v[j]) {
+        if      ops.less(&v[j], &v[k]) { j }
+        else if ops.less(&v[i], &v[k]) { k }
+        else                           { i }
+    } else {
+        if      ops.less(&v[i], &v[k]) { i }
+        else if ops.less(&v[j], &v[k]) { k }
+        else                           { j }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{partition, sort};
-    use super::super::tests::test_sort;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use std::thread;
+    use super::super::{is_sorted, Ops};
+    use super::{median_of_3, par_sort, par_sort_by, par_sort_by_key,
+                par_sort_part, partition, partition_3way, sort, sort_3way,
+                sort_3way_by, sort_3way_by_key, sort_3way_with_stats, sort_by,
+                sort_by_key, sort_with_stats, STABLE};
+    use super::super::tests::{lg, random_permutation, stats_for, test_sort,
+                              test_sort_by_key, test_sort_descending,
+                              test_sort_no_clone, test_sort_strings,
+                              test_stability};
 
-    fn is_partitioned<T: Ord>(v: &Vec<T>,
+    fn is_partitioned<T: Ord>(v: &[T],
                               lo: usize,
                               hi: usize,
                               i: usize) -> bool {
@@ -69,7 +314,9 @@ mod tests {
             let lo = 2;
             let hi = n - 2;
 
-            let i = partition(&mut output, lo, hi);
+            let mut rec = ();
+            let mut ops = Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+            let i = partition(&mut output, lo, hi, &mut ops);
 
             for j in 0..lo { assert!(input[j] == output[j]); }
             for j in hi..n { assert!(input[j] == output[j]); }
@@ -81,4 +328,182 @@ mod tests {
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_works_for_strings() {
+        test_sort_strings(sort);
+    }
+
+    #[test]
+    fn sort_works_without_clone() {
+        test_sort_no_clone(sort);
+    }
+
+    #[test]
+    fn sort_with_stats_counts_compares() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        // ~2N ln N = 1.39 N lg N compares on average
+        assert!((stats.compares as f64) < 2.0 * n as f64 * lg(n));
+        assert!((stats.compares as f64) > n as f64 * lg(n));
+
+        // quadratic for sorted input
+        let n = 100;
+        let sorted: Vec<isize> = (0..n as isize).collect();
+        let stats = stats_for(sort_with_stats, &sorted);
+        assert!(stats.compares >= n*(n-1)/2);
+    }
+
+    #[test]
+    fn median_of_3_works() {
+        let mut rec = ();
+        let mut ops = Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+        for v in &[[1, 2, 3], [1, 3, 2], [2, 1, 3],
+                   [2, 3, 1], [3, 1, 2], [3, 2, 1]] {
+            let m = median_of_3(v, 0, 1, 2, &mut ops);
+            assert_eq!(2, v[m]);
+        }
+        assert_eq!(5, [5, 5, 5][median_of_3(&[5, 5, 5], 0, 1, 2, &mut ops)]);
+    }
+
+    #[test]
+    fn partition_3way_works() {
+        let mut rec = ();
+        let mut ops = Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+        let mut v = vec![9, 3, 5, 1, 5, 8, 5, 2, 7, 5, 4, 9];
+        let (lt, gt) = partition_3way(&mut v, 1, 11, &mut ops);
+        assert_eq!(v[0], 9);
+        assert_eq!(v[11], 9);
+        assert!(v[1..lt].iter().all(|&x| x < v[lt]));
+        assert!(v[lt..gt].iter().all(|&x| x == v[lt]));
+        assert!(v[gt..11].iter().all(|&x| x > v[lt]));
+    }
+
+    #[test]
+    fn sort_3way_works() {
+        test_sort(sort_3way);
+        test_sort_descending(|v| sort_3way_by(v, |a, b| b.cmp(a)));
+        test_sort_by_key(|v| sort_3way_by_key(v, |r| r.0));
+        test_sort_strings(sort_3way);
+        test_sort_no_clone(sort_3way);
+    }
+
+    #[test]
+    fn sort_3way_works_for_larger_inputs() {
+        for &n in &[11, 12, 100, 1000] {
+            let mut v = random_permutation(n, n);
+            sort_3way(&mut v);
+            assert_eq!(v, (0..n as isize).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn sort_3way_all_equal() {
+        let n = 1000;
+        let input = vec![7; n];
+        let stats = stats_for(sort_3way_with_stats, &input);
+        // one partitioning pass, after choosing the pivot
+        assert!(stats.compares <= n + 2);
+        // vs. ~N lg N for 2-way partitioning
+        let stats_2way = stats_for(sort_with_stats, &input);
+        assert!(stats_2way.compares > 5 * stats.compares);
+    }
+
+    #[test]
+    fn sort_3way_few_distinct_keys() {
+        let n = 10000;
+        let input: Vec<isize> =
+            random_permutation(n, 1).iter().map(|x| x % 3).collect();
+        let mut v = input.clone();
+        sort_3way(&mut v);
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(v, expected);
+        // each of the 3 keys ends up as a pivot once, roughly
+        let stats = stats_for(sort_3way_with_stats, &input);
+        assert!(stats.compares < 4*n);
+    }
+
+    #[test]
+    fn sort_is_not_stable() {
+        test_stability(|v| sort_by_key(v, |r| r.0), STABLE);
+    }
+
+    #[test]
+    fn sort_3way_is_not_stable() {
+        test_stability(|v| sort_3way_by_key(v, |r| r.0), STABLE);
+    }
+
+    #[test]
+    fn par_sort_works() {
+        test_sort(|v| par_sort(v, 4));
+        test_sort_descending(|v| par_sort_by(v, 2, |a, b| b.cmp(a)));
+        test_sort_by_key(|v| par_sort_by_key(v, 3, |r| r.0));
+        test_sort_strings(|v| par_sort(v, 2));
+        test_sort_no_clone(|v| par_sort(v, 2));
+        for &threads in &[1, 2, 3, 4, 7] {
+            let mut v = random_permutation(100000, threads);
+            par_sort(&mut v, threads);
+            assert_eq!(v, (0..100000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn par_sort_works_for_sorted_and_equal_keys() {
+        let mut v: Vec<isize> = (0..100000).collect();
+        par_sort(&mut v, 4);
+        assert_eq!(v, (0..100000).collect::<Vec<_>>());
+        v.reverse();
+        par_sort(&mut v, 4);
+        assert_eq!(v, (0..100000).collect::<Vec<_>>());
+        let mut v = vec![7; 100000];
+        par_sort(&mut v, 4);
+        assert!(v.iter().all(|&x| x == 7));
+    }
+
+    #[test]
+    fn par_sort_uses_at_most_the_threads_given() {
+        let used = Mutex::new(HashSet::new());
+        let mut v = random_permutation(100000, 1);
+        v.sort();
+        v.extend(vec![0; 50000]);
+        par_sort_by(&mut v, 4, |a, b| {
+            used.lock().unwrap().insert(thread::current().id());
+            a.cmp(b)
+        });
+        assert!(is_sorted(&v));
+        assert!(used.lock().unwrap().len() <= 4);
+    }
+
+    // Sort with parallel splits down to small subarrays.
+    fn par_sort_small<T: Ord + Send>(v: &mut [T], threads: usize) {
+        par_sort_part(v, threads, 4, &|a: &T, b: &T| a.cmp(b));
+    }
+
+    #[test]
+    fn par_sort_splits_small_inputs() {
+        for &threads in &[2, 3, 5, 16, 64] {
+            test_sort(|v| par_sort_small(v, threads));
+            test_sort_no_clone(|v| par_sort_small(v, threads));
+            let mut v = random_permutation(1000, threads);
+            par_sort_small(&mut v, threads);
+            assert_eq!(v, (0..1000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn par_sort_needs_a_thread() {
+        par_sort(&mut [2, 1], 0);
+    }
 }
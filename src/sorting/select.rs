@@ -0,0 +1,285 @@
+//! Selection of order statistics: quickselect, medians and percentiles.
+//!
+//! `select_kth` partitions a slice around its `k`-th smallest element in
+//! linear time, using the partitioning from quicksort. Pivots are chosen at
+//! random, which takes about 2N + 2k ln(N/k) + 2(N-k) ln(N/(N-k)) compares on
+//! average. To guarantee linear time on every input, the search switches to
+//! the median-of-medians pivot (Blum, Floyd, Pratt, Rivest and Tarjan, 1973)
+//! when the random pivots have partitioned too many elements in total, as in
+//! Musser's introselect.
+
+use std::cmp::{self, Ordering};
+
+use rand::{self, Rng};
+
+use super::Ops;
+use super::insertion;
+use super::intro;
+use super::quick;
+use super::stats::Recorder;
+
+// Subarrays with at most this many elements are sorted with insertion sort.
+const CUTOFF: usize = 10;
+
+// Random pivots may partition subarrays with at most this many times N
+// elements in total, more than twice the average for finding the median.
+const WORK_LIMIT: usize = 8;
+
+/// Rearrange `v` so that `v[k]` is the element that would be there if `v` were
+/// sorted, with no greater elements before it and no smaller ones after it.
+/// Returns a reference to `v[k]`.
+///
+/// # Panics
+///
+/// Panics if `k >= v.len()`.
+pub fn select_kth<T: Ord>(v: &mut [T], k: usize) -> &T {
+    select_kth_by(v, k, |a, b| a.cmp(b))
+}
+
+/// Like `select_kth`, with the order defined by `compare`.
+pub fn select_kth_by<T, F>(v: &mut [T], k: usize, compare: F) -> &T
+    where F: FnMut(&T, &T) -> Ordering {
+    assert!(k < v.len(), "index {} out of range for length {}", k, v.len());
+    select_ops(v, k, true, &mut Ops::new(compare, &mut ()));
+    &v[k]
+}
+
+/// Return the median of `v`, or `None` if `v` is empty. For an even number of
+/// elements this is the lower of the two middle ones. Rearranges `v` as
+/// `select_kth` does.
+pub fn median<T: Ord>(v: &mut [T]) -> Option<&T> {
+    if v.is_empty() { return None; }
+    let k = (v.len() - 1)/2;
+    Some(select_kth(v, k))
+}
+
+/// Return the `p`-th percentile of `v` by the nearest-rank method: the
+/// smallest element that's greater than or equal to at least `p` percent of
+/// the elements. Returns `None` if `v` is empty, and rearranges `v` as
+/// `select_kth` does.
+///
+/// # Panics
+///
+/// Panics if `p` isn't between 0 and 100.
+pub fn nth_percentile<T: Ord>(v: &mut [T], p: f64) -> Option<&T> {
+    assert!((0.0..=100.0).contains(&p), "percentile {} out of range", p);
+    if v.is_empty() { return None; }
+    let rank = (p / 100.0 * v.len() as f64).ceil() as usize;
+    let k = cmp::max(rank, 1) - 1;
+    Some(select_kth(v, k))
+}
+
+/// Rearrange `v` so that `v[..k]` holds its `k` smallest elements in sorted
+/// order. The order of the other elements is unspecified. If `k >= v.len()`,
+/// sorts all of `v`.
+pub fn partial_sort<T: Ord>(v: &mut [T], k: usize) {
+    partial_sort_by(v, k, |a, b| a.cmp(b));
+}
+
+/// Like `partial_sort`, with the order defined by `compare`.
+pub fn partial_sort_by<T, F>(v: &mut [T], k: usize, compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    let mut rec = ();
+    let ops = &mut Ops::new(compare, &mut rec);
+    let k = cmp::min(k, v.len());
+    if k == 0 { return; }
+    if k < v.len() {
+        select_ops(v, k-1, true, ops);
+    }
+    intro::sort_ops(&mut v[..k], ops);
+}
+
+// Partition `v` around its `k`-th smallest element, choosing pivots at random
+// if `random` is true and with the median of medians otherwise.
+fn select_ops<T, F, R>(v: &mut [T], k: usize, mut random: bool,
+                       ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let mut rng = rand::thread_rng();
+    let (mut lo, mut hi) = (0, v.len());
+    let mut budget = WORK_LIMIT * v.len();
+    loop {
+        if hi <= lo + CUTOFF {
+            insertion::sort_ops(&mut v[lo..hi], ops);
+            return;
+        }
+        // give up on random pivots once they've done more work than they
+        // should on average
+        if hi - lo > budget { random = false; }
+        let p = if random { budget -= hi - lo; rng.gen_range(lo, hi) }
+                else      { median_of_medians(v, lo, hi, ops) };
+        ops.exch(v, lo, p);
+        let j = quick::partition(v, lo, hi, ops);
+        if      k < j { hi = j; }
+        else if k > j { lo = j+1; }
+        else          { return; }
+    }
+}
+
+// Return the index of a pivot in `v[lo..hi]` that has at least about 3/10 of
+// the elements on either side of it. The elements are divided into groups of
+// 5, and the median of each group is moved to the front; the pivot is the
+// median of those.
+fn median_of_medians<T, F, R>(v: &mut [T], lo: usize, hi: usize,
+                              ops: &mut Ops<F, R>) -> usize
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
+    let mut m = lo;
+    let mut i = lo;
+    while i < hi {
+        let end = cmp::min(i+5, hi);
+        insertion::sort_ops(&mut v[i..end], ops);
+        ops.exch(v, m, i + (end - i)/2);
+        m += 1;
+        i = end;
+    }
+    let mid = (m - lo)/2;
+    select_ops(&mut v[lo..m], mid, false, ops);
+    lo + mid
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use super::{median, median_of_medians, nth_percentile, partial_sort,
+                partial_sort_by, select_kth, select_kth_by, select_ops};
+    use super::super::Ops;
+    use super::super::stats::SortStats;
+    use super::super::tests::{mcilroy_killer, random_permutation};
+
+    // Check that `v` is partitioned around `v[k]`.
+    fn is_selected(v: &[isize], k: usize) -> bool {
+        v[..k].iter().all(|&x| x <= v[k]) &&
+            v[k+1..].iter().all(|&x| x >= v[k])
+    }
+
+    #[test]
+    fn select_kth_works() {
+        for &n in &[1, 2, 10, 11, 50, 1000] {
+            let input = random_permutation(n, n);
+            for k in 0..n {
+                let mut v = input.clone();
+                assert_eq!(*select_kth(&mut v, k), k as isize);
+                assert!(is_selected(&v, k));
+            }
+        }
+    }
+
+    #[test]
+    fn select_kth_works_with_duplicates() {
+        let input: Vec<isize> =
+            random_permutation(500, 1).iter().map(|x| x % 7).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+        for (k, x) in sorted.iter().enumerate() {
+            let mut v = input.clone();
+            assert_eq!(select_kth(&mut v, k), x);
+            assert!(is_selected(&v, k));
+        }
+    }
+
+    #[test]
+    fn select_kth_by_works() {
+        let mut v = random_permutation(100, 2);
+        assert_eq!(*select_kth_by(&mut v, 0, |a, b| b.cmp(a)), 99);
+        assert_eq!(*select_kth_by(&mut v, 10, |a, b| b.cmp(a)), 89);
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_kth_out_of_range() {
+        select_kth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn median_works() {
+        assert_eq!(median::<isize>(&mut []), None);
+        assert_eq!(median(&mut [7]), Some(&7));
+        assert_eq!(median(&mut [3, 1, 2]), Some(&2));
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(&2));
+        let mut v = random_permutation(1001, 3);
+        assert_eq!(median(&mut v), Some(&500));
+    }
+
+    #[test]
+    fn nth_percentile_works() {
+        let mut v: Vec<isize> =
+            random_permutation(100, 4).iter().map(|x| x + 1).collect();
+        assert_eq!(nth_percentile(&mut v, 0.0), Some(&1));
+        assert_eq!(nth_percentile(&mut v, 50.0), Some(&50));
+        assert_eq!(nth_percentile(&mut v, 90.0), Some(&90));
+        assert_eq!(nth_percentile(&mut v, 99.5), Some(&100));
+        assert_eq!(nth_percentile(&mut v, 100.0), Some(&100));
+        assert_eq!(nth_percentile::<isize>(&mut [], 50.0), None);
+        assert_eq!(nth_percentile(&mut [15, 20, 35, 40, 50], 30.0), Some(&20));
+    }
+
+    #[test]
+    #[should_panic]
+    fn nth_percentile_out_of_range() {
+        nth_percentile(&mut [1, 2, 3], 101.0);
+    }
+
+    #[test]
+    fn partial_sort_works() {
+        let input = random_permutation(1000, 5);
+        for &k in &[0, 1, 10, 500, 999, 1000, 2000] {
+            let mut v = input.clone();
+            partial_sort(&mut v, k);
+            let k = if k > 1000 { 1000 } else { k };
+            assert_eq!(&v[..k], &(0..k as isize).collect::<Vec<_>>()[..]);
+            v.sort();
+            assert_eq!(v, (0..1000).collect::<Vec<_>>());
+        }
+
+        let mut v = random_permutation(100, 6);
+        partial_sort_by(&mut v, 3, |a, b| b.cmp(a));
+        assert_eq!(&v[..3], &[99, 98, 97]);
+    }
+
+    #[test]
+    fn median_of_medians_is_near_the_middle() {
+        let n = 1000;
+        for seed in 0..5 {
+            let mut v = random_permutation(n, seed);
+            let mut rec = ();
+            let mut ops = Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut rec);
+            let m = median_of_medians(&mut v, 0, n, &mut ops);
+            assert!(v[m] >= 3*n as isize/10 - 3);
+            assert!(v[m] <= 7*n as isize/10 + 3);
+        }
+    }
+
+    fn compares_for(input: &[isize], k: usize, random: bool) -> usize {
+        let mut v = input.to_vec();
+        let mut stats = SortStats::new();
+        select_ops(&mut v, k, random,
+                   &mut Ops::new(|a: &isize, b: &isize| a.cmp(b), &mut stats));
+        assert_eq!(v[k], k as isize);
+        stats.compares
+    }
+
+    #[test]
+    fn select_takes_linear_compares() {
+        let n = 100000;
+        let shuffled = random_permutation(n, 7);
+        let sorted: Vec<isize> = (0..n as isize).collect();
+        for input in &[shuffled, sorted] {
+            // 2(1 + ln 2)N = 3.39N for the median on average
+            assert!(compares_for(input, n/2, true) < 8*n);
+            assert!(compares_for(input, n/2, false) < 30*n);
+            assert!(compares_for(input, 0, false) < 30*n);
+        }
+    }
+
+    #[test]
+    fn select_kth_survives_mcilroy_killer() {
+        let n = 10000;
+        let compares = Cell::new(0);
+        mcilroy_killer(n, |v, compare| {
+            select_kth_by(v, n/2, |a, b| {
+                compares.set(compares.get() + 1);
+                compare(a, b)
+            });
+        });
+        assert!(compares.get() < 30*n);
+    }
+}
@@ -1,26 +1,98 @@
 //! Algorithm 2.1: Selection sort.
 
+use std::cmp::Ordering;
+
+use super::{Ops, Sorter};
+use super::stats::{Recorder, SortStats};
+
+/// Selection sort is not stable: exchanging the minimum into place can move
+/// an element past others equal to it.
+pub const STABLE: bool = false;
+
 /// Sort `v` using selection sort.
-pub fn sort<T: Ord>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using selection sort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using selection sort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using selection sort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Selection sort as a `Sorter`.
+pub struct Selection;
+
+impl<T: Ord> Sorter<T> for Selection {
+    fn name(&self) -> &'static str { "selection" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+// Sort `v`, using `ops` for compares and exchanges.
+fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
     for i in 0..n {
         let mut min = i;
         for j in i+1..n {
-            if v[j] < v[min] {
+            if ops.less(&v[j], &v[min]) {
                 min = j;
             }
         }
-        v.swap(i, min);
+        ops.exch(v, i, min);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::tests::test_sort;
+    use super::{sort, sort_by, sort_by_key, sort_with_stats, STABLE};
+    use super::super::tests::{random_permutation, stats_for, test_sort,
+                              test_sort_by_key, test_sort_descending,
+                              test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_with_stats_counts_quadratic_compares() {
+        let n = 100;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        assert_eq!(stats.compares, n*(n-1)/2);
+        assert_eq!(stats.exchanges, n);
+        assert_eq!(stats.array_accesses(), 2*stats.compares + 4*n);
+    }
+
+    #[test]
+    fn stability_matches_marker() {
+        test_stability(|v| sort_by_key(v, |r| r.0), STABLE);
+    }
 }
@@ -1,7 +1,53 @@
 //! Algorithm 2.3 Shell sort.
 
+use std::cmp::Ordering;
+
+use super::{Ops, Sorter};
+use super::stats::{Recorder, SortStats};
+
+/// Shellsort is not stable: h-sorting moves elements past others that lie
+/// between them.
+pub const STABLE: bool = false;
+
 /// Sort `v` using shell sort.
-pub fn sort<T: Ord>(v: &mut Vec<T>) {
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort_by(v, |a, b| a.cmp(b));
+}
+
+/// Sort `v` using shell sort, with the order defined by `compare`.
+pub fn sort_by<T, F>(v: &mut [T], compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    sort_ops(v, &mut Ops::new(compare, &mut ()));
+}
+
+/// Sort `v` using shell sort, comparing the keys extracted by `key`.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut key: F)
+    where K: Ord, F: FnMut(&T) -> K {
+    sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort `v` using shell sort, adding the number of operations to `stats`.
+pub fn sort_with_stats<T: Ord>(v: &mut [T], stats: &mut SortStats) {
+    sort_ops(v, &mut Ops::new(|a: &T, b: &T| a.cmp(b), stats));
+}
+
+/// Shellsort as a `Sorter`.
+pub struct Shell;
+
+impl<T: Ord> Sorter<T> for Shell {
+    fn name(&self) -> &'static str { "shell" }
+    fn is_stable(&self) -> bool { STABLE }
+    fn is_in_place(&self) -> bool { true }
+    fn sort(&self, v: &mut [T]) { sort(v); }
+    fn sort_by(&self, v: &mut [T],
+               compare: &mut dyn FnMut(&T, &T) -> Ordering) {
+        sort_by(v, compare);
+    }
+}
+
+// Sort `v`, using `ops` for compares and exchanges.
+fn sort_ops<T, F, R>(v: &mut [T], ops: &mut Ops<F, R>)
+    where F: FnMut(&T, &T) -> Ordering, R: Recorder {
     let n = v.len();
 
     let mut h = 1;
@@ -12,8 +58,8 @@ pub fn sort<T: Ord>(v: &mut Vec<T>) {
     while h >= 1 {
         for i in h..n {
             let mut j = i;
-            while j >= h && v[j] < v[j-h] {
-                v.swap(j, j-h);
+            while j >= h && ops.less(&v[j], &v[j-h]) {
+                ops.exch(v, j, j-h);
                 j -= h;
             }
         }
@@ -23,11 +69,36 @@ pub fn sort<T: Ord>(v: &mut Vec<T>) {
 
 #[cfg(test)]
 mod tests {
-    use super::sort;
-    use super::super::tests::test_sort;
+    use super::{sort, sort_by, sort_by_key, sort_with_stats, STABLE};
+    use super::super::tests::{random_permutation, stats_for, test_sort,
+                              test_sort_by_key, test_sort_descending,
+                              test_stability};
 
     #[test]
     fn sort_works() {
         test_sort(sort);
     }
+
+    #[test]
+    fn sort_by_works() {
+        test_sort_descending(|v| sort_by(v, |a, b| b.cmp(a)));
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        test_sort_by_key(|v| sort_by_key(v, |r| r.0));
+    }
+
+    #[test]
+    fn sort_with_stats_counts_subquadratic_compares() {
+        let n = 1000;
+        let stats = stats_for(sort_with_stats, &random_permutation(n, 1));
+        assert!((stats.compares as f64) < (n as f64).powf(1.5));
+        assert!(stats.exchanges <= stats.compares);
+    }
+
+    #[test]
+    fn stability_matches_marker() {
+        test_stability(|v| sort_by_key(v, |r| r.0), STABLE);
+    }
 }
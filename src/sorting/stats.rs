@@ -0,0 +1,100 @@
+//! Counting the operations performed by sorting algorithms.
+//!
+//! The analysis of sorting algorithms in the book counts compares and
+//! exchanges, and array accesses. Every algorithm in `sorting` has a
+//! `sort_with_stats` function that adds those counts to a `SortStats`.
+//!
+//! A compare reads two elements and an exchange reads and writes two, and
+//! since the sorts move elements only by exchanging them, those are all the
+//! accesses to the array being sorted. Merge sorts also read and write the
+//! indices in their auxiliary array, and report those with `read` and
+//! `write`.
+
+/// Counts of the basic operations performed by a sort.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SortStats {
+    /// number of calls to the comparison function
+    pub compares: usize,
+    /// number of times two elements were swapped
+    pub exchanges: usize,
+    /// number of times an entry of the array or an auxiliary array was read
+    pub reads: usize,
+    /// number of times an entry of the array or an auxiliary array was
+    /// written
+    pub writes: usize,
+}
+
+impl SortStats {
+    /// Constructs a new `SortStats` with all counts zero.
+    pub fn new() -> SortStats {
+        SortStats::default()
+    }
+
+    /// Returns the total number of array accesses.
+    pub fn array_accesses(&self) -> usize {
+        self.reads + self.writes
+    }
+}
+
+/// Receives notifications of the operations a sort performs. Implemented by
+/// `SortStats`, and by `()`, which ignores them.
+pub trait Recorder {
+    /// Called when two elements are compared.
+    fn compare(&mut self);
+
+    /// Called when two elements are exchanged.
+    fn exchange(&mut self);
+
+    /// Called when an entry of an auxiliary array is read.
+    fn read(&mut self);
+
+    /// Called when an entry of an auxiliary array is written.
+    fn write(&mut self);
+}
+
+impl Recorder for SortStats {
+    fn compare(&mut self) {
+        self.compares += 1;
+        self.reads += 2;
+    }
+
+    fn exchange(&mut self) {
+        self.exchanges += 1;
+        self.reads += 2;
+        self.writes += 2;
+    }
+
+    fn read(&mut self) {
+        self.reads += 1;
+    }
+
+    fn write(&mut self) {
+        self.writes += 1;
+    }
+}
+
+impl Recorder for () {
+    fn compare(&mut self) {}
+    fn exchange(&mut self) {}
+    fn read(&mut self) {}
+    fn write(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Recorder, SortStats};
+
+    #[test]
+    fn counts_operations() {
+        let mut stats = SortStats::new();
+        stats.compare();
+        stats.compare();
+        stats.exchange();
+        stats.read();
+        stats.write();
+        assert_eq!(stats, SortStats {
+            compares: 2, exchanges: 1, reads: 7, writes: 3,
+        });
+        assert_eq!(stats.array_accesses(), 10);
+    }
+}
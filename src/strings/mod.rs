@@ -0,0 +1,3 @@
+//! Implementations of data structures and algorithms from Chapter 5: Strings.
+
+pub mod sort;
@@ -0,0 +1,87 @@
+//! Key-indexed counting (page 703).
+//!
+//! To sort by integer keys in `0..r`, count how many elements have each key,
+//! compute from the counts where the elements with each key start, and move
+//! each element to the next position for its key. That takes time
+//! proportional to N + r, with no compares.
+
+use super::permute;
+
+/// Key-indexed counting is stable: elements with the same key are moved to
+/// consecutive positions in the order they're in.
+pub const STABLE: bool = true;
+
+/// Sort `v` by the keys extracted by `key`, which must be less than `r`.
+///
+/// # Panics
+///
+/// Panics if a key is `r` or greater.
+pub fn sort_by_key<T, F>(v: &mut [T], r: usize, key: F)
+    where F: Fn(&T) -> usize {
+    // count[k + 1] is the number of elements with key k
+    let mut count = vec![0; r + 1];
+    for x in v.iter() {
+        let k = key(x);
+        assert!(k < r, "key {} out of range for radix {}", k, r);
+        count[k + 1] += 1;
+    }
+    // count[k] is the position of the first element with key k
+    for k in 0..r {
+        count[k + 1] += count[k];
+    }
+    let mut dest: Vec<usize> = v.iter().map(|x| {
+        let k = key(x);
+        count[k] += 1;
+        count[k] - 1
+    }).collect();
+    permute(v, &mut dest);
+}
+
+#[cfg(test)]
+mod tests {
+    use sorting::merge::top_down;
+    use super::sort_by_key;
+    use super::super::tests::random_u64s;
+
+    #[test]
+    fn sort_by_key_works() {
+        // students and their sections (page 703)
+        let mut v = vec![
+            ("Anderson", 2), ("Brown", 3), ("Davis", 3), ("Garcia", 4),
+            ("Harris", 1), ("Jackson", 3), ("Johnson", 4), ("Jones", 3),
+            ("Martin", 1), ("Martinez", 2), ("Miller", 2), ("Moore", 1),
+            ("Robinson", 2), ("Smith", 4), ("Taylor", 3), ("Thomas", 4),
+            ("Thompson", 4), ("White", 2), ("Williams", 3), ("Wilson", 4),
+        ];
+        let mut expected = v.clone();
+        top_down::sort_by_key(&mut expected, |s| s.1);
+        sort_by_key(&mut v, 5, |s| s.1);
+        assert_eq!(v, expected);
+        assert_eq!(v[0], ("Harris", 1));
+        assert_eq!(v[3], ("Anderson", 2));
+    }
+
+    #[test]
+    fn sort_by_key_is_stable() {
+        let v: Vec<(usize, usize)> = random_u64s(1000, 1).iter()
+            .enumerate().map(|(i, &x)| (x as usize % 10, i)).collect();
+        let mut sorted = v.clone();
+        sort_by_key(&mut sorted, 10, |r| r.0);
+        let mut expected = v;
+        expected.sort();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn sort_by_key_of_nothing() {
+        let mut v: Vec<usize> = vec![];
+        sort_by_key(&mut v, 0, |&x| x);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn sort_by_key_out_of_range() {
+        sort_by_key(&mut [1, 5, 2], 5, |&x| x);
+    }
+}
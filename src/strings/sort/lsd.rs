@@ -0,0 +1,200 @@
+//! Algorithm 5.1 LSD string sort, and LSD radix sort for integers.
+//!
+//! LSD string sort sorts strings of a fixed length `w` by key-indexed counting
+//! on each byte, from the last to the first, which takes time proportional to
+//! wN. Since key-indexed counting is stable, strings that agree on the byte of
+//! a pass stay in the order of the passes before it.
+//!
+//! Integers are sorted the same way, one byte at a time from the least
+//! significant, skipping the bytes that are the same in every element. For
+//! signed integers, the sign bit is flipped so that negative numbers come
+//! before positive ones.
+
+use std::mem;
+
+use super::{key_indexed, R};
+
+/// LSD sorts are stable, since key-indexed counting is.
+pub const STABLE: bool = true;
+
+/// Sort `v` by the first `w` bytes of each string.
+///
+/// # Panics
+///
+/// Panics if a string is shorter than `w` bytes.
+pub fn sort<S: AsRef<[u8]>>(v: &mut [S], w: usize) {
+    for s in v.iter() {
+        let len = s.as_ref().len();
+        assert!(len >= w, "string of {} bytes is shorter than {}", len, w);
+    }
+    for d in (0..w).rev() {
+        key_indexed::sort_by_key(v, R, |s| s.as_ref()[d] as usize);
+    }
+}
+
+/// Sort `v` using LSD radix sort.
+pub fn sort_u32(v: &mut [u32]) {
+    sort_digits(v, mem::size_of::<u32>(), |&x| x as u64);
+}
+
+/// Sort `v` using LSD radix sort.
+pub fn sort_u64(v: &mut [u64]) {
+    sort_digits(v, mem::size_of::<u64>(), |&x| x);
+}
+
+/// Sort `v` using LSD radix sort. The sign bit of each key is flipped before
+/// its bytes are sorted as unsigned digits, so that negative numbers sort
+/// before non-negative ones.
+pub fn sort_i64(v: &mut [i64]) {
+    sort_digits(v, mem::size_of::<i64>(), |&x| (x as u64) ^ (1 << 63));
+}
+
+// Sort `v` by the `bytes` least significant bytes of the keys extracted by
+// `key`, using `aux` to distribute each byte.
+fn sort_digits<T, F>(v: &mut [T], bytes: usize, key: F)
+    where T: Copy, F: Fn(&T) -> u64 {
+    let n = v.len();
+    let mut aux = v.to_vec();
+    for b in 0..bytes {
+        let digit = |x: &T| (key(x) >> (8*b)) as usize & (R - 1);
+        // count[r + 1] is the number of elements whose digit is r
+        let mut count = [0; R + 1];
+        for x in v.iter() {
+            count[digit(x) + 1] += 1;
+        }
+        // this byte doesn't change the order
+        if count.contains(&n) { continue; }
+        for r in 0..R {
+            count[r + 1] += count[r];
+        }
+        for x in v.iter() {
+            let r = digit(x);
+            aux[count[r]] = *x;
+            count[r] += 1;
+        }
+        v.copy_from_slice(&aux);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sorting::merge::top_down;
+    use std::cell::Cell;
+    use super::{sort, sort_digits, sort_i64, sort_u32, sort_u64};
+    use super::super::tests::{random_strings, random_u64s};
+
+    #[test]
+    fn sort_works() {
+        // license plates (page 707)
+        let mut v = vec!["4PGC938", "2IYE230", "3CIO720", "1ICK750", "1OHV845",
+                         "4JZY524", "1ICK750", "3CIO720", "1OHV845", "1OHV845",
+                         "2RLA629", "2RLA629", "3ATW723"];
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort(&mut v, 7);
+        assert_eq!(v, expected);
+        assert_eq!(v[0], "1ICK750");
+        assert_eq!(v[12], "4PGC938");
+    }
+
+    #[test]
+    fn sort_works_for_random_strings() {
+        let input = random_strings(2000, 10, 10, "ACGT", 1);
+        let mut expected = input.clone();
+        top_down::sort(&mut expected);
+        let mut v = input.clone();
+        sort(&mut v, 10);
+        assert_eq!(v, expected);
+
+        let mut bytes: Vec<&[u8]> = input.iter().map(|s| s.as_bytes())
+                                         .collect();
+        sort(&mut bytes, 10);
+        assert!(bytes.iter().zip(&expected).all(|(a, b)| *a == b.as_bytes()));
+    }
+
+    #[test]
+    fn sort_uses_only_the_first_w_bytes() {
+        // stable, so ties on the first 2 bytes keep their order
+        let mut v = vec!["bbz", "aay", "bba", "aax"];
+        sort(&mut v, 2);
+        assert_eq!(v, vec!["aay", "aax", "bbz", "bba"]);
+        sort(&mut v, 0);
+        assert_eq!(v, vec!["aay", "aax", "bbz", "bba"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "shorter")]
+    fn sort_needs_w_bytes() {
+        sort(&mut ["abc", "ab", "abd"], 3);
+    }
+
+    #[test]
+    fn sort_integers() {
+        let input = random_u64s(5000, 2);
+
+        let mut v = input.clone();
+        let mut expected = input.clone();
+        v.extend(&[0, u64::MAX, 1 << 32]);
+        expected.extend(&[0, u64::MAX, 1 << 32]);
+        top_down::sort(&mut expected);
+        sort_u64(&mut v);
+        assert_eq!(v, expected);
+
+        let mut v: Vec<u32> = input.iter().map(|&x| x as u32).collect();
+        v.extend(&[0, u32::MAX]);
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort_u32(&mut v);
+        assert_eq!(v, expected);
+
+        let mut v: Vec<i64> = input.iter().map(|&x| x as i64).collect();
+        v.extend(&[0, -1, 1, i64::MIN, i64::MAX]);
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort_i64(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn sort_integers_with_few_distinct_bytes() {
+        // numbers below 256 differ only in the low byte, so the passes for
+        // the other bytes are skipped
+        let mut v: Vec<u64> = (0..1000).map(|x| (x * 7919) % 256).collect();
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort_u64(&mut v);
+        assert_eq!(v, expected);
+
+        // so are numbers from 0 to 255 once their sign bit is flipped, since
+        // it's the same in all of them
+        let mut v: Vec<i64> = (0..1000).map(|x| (x * 7919) % 256).collect();
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort_i64(&mut v);
+        assert_eq!(v, expected);
+
+        // numbers from -128 to 127 differ in every byte after the flip, so
+        // no pass is skipped
+        let mut v: Vec<i64> = (0..1000).map(|x| (x * 7919) % 256 - 128)
+                                       .collect();
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort_i64(&mut v);
+        assert_eq!(v, expected);
+
+        let mut v: Vec<u64> = vec![];
+        sort_u64(&mut v);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn sort_digits_skips_bytes_that_agree() {
+        // count the passes through the key function: 1 to count each byte,
+        // and 1 more to distribute a byte that isn't skipped
+        let calls = Cell::new(0);
+        let mut v: Vec<u64> = vec![3, 0x0100, 1, 2];
+        sort_digits(&mut v, 8, |&x| { calls.set(calls.get() + 1); x });
+        assert_eq!(v, vec![1, 2, 3, 0x0100]);
+        assert_eq!(calls.get(), (8 + 2) * v.len());
+    }
+}
@@ -0,0 +1,107 @@
+//! String sorts (Section 5.1).
+//!
+//! Instead of comparing whole keys, these sorts look at one digit of a key at
+//! a time: a byte of a string, or a byte of an integer. `key_indexed` sorts by
+//! small integer keys in linear time, `lsd` sorts strings of a fixed length
+//! and integers from the last digit to the first, and `msd` sorts strings of
+//! any length from the first digit. `quick3` combines MSD string sort with
+//! quicksort, which suits strings with long common prefixes. Strings are
+//! compared as byte strings, so any type that's `AsRef<[u8]>`, like `String`,
+//! `&str` or `&[u8]`, can be sorted, and a string comes before the strings
+//! it's a prefix of.
+//!
+//! Like the sorts in `sorting`, the string sorts and key-indexed counting move
+//! elements only by swapping, so the string types needn't be `Clone`. The
+//! integer sorts in `lsd` copy the integers through an auxiliary array
+//! instead. Each module has a `STABLE` constant.
+
+pub mod key_indexed;
+pub mod lsd;
+pub mod msd;
+pub mod quick3;
+
+// The number of values of a digit: a byte.
+const R: usize = 256;
+
+// Move the element at position `i` to position `dest[i]`, for each `i`, by
+// following the cycles of the permutation. Leaves `dest[i] == i`.
+fn permute<T>(v: &mut [T], dest: &mut [usize]) {
+    for i in 0..v.len() {
+        while dest[i] != i {
+            let d = dest[i];
+            v.swap(i, d);
+            dest.swap(i, d);
+        }
+    }
+}
+
+// Return byte `d` of `s` plus 1, or 0 if `s` has no byte `d`.
+fn digit<S: AsRef<[u8]>>(s: &S, d: usize) -> usize {
+    s.as_ref().get(d).map_or(0, |&b| b as usize + 1)
+}
+
+// Sort `v`, whose strings agree on their first `d` bytes, by the rest of the
+// bytes.
+fn insertion_sort<S: AsRef<[u8]>>(v: &mut [S], d: usize) {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && v[j].as_ref()[d..] < v[j - 1].as_ref()[d..] {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, StdRng};
+    use super::{digit, insertion_sort, permute};
+
+    #[test]
+    fn permute_works() {
+        let mut v = vec!['c', 'a', 'd', 'b', 'e'];
+        let mut dest = vec![2, 0, 3, 1, 4];
+        permute(&mut v, &mut dest);
+        assert_eq!(v, vec!['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(dest, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn digit_works() {
+        assert_eq!(digit(&"ab", 0), b'a' as usize + 1);
+        assert_eq!(digit(&"ab", 1), b'b' as usize + 1);
+        assert_eq!(digit(&"ab", 2), 0);
+        assert_eq!(digit(&"", 0), 0);
+    }
+
+    #[test]
+    fn insertion_sort_from_digit_d() {
+        let mut v = vec!["xxb", "xxa", "xx", "xxab"];
+        insertion_sort(&mut v, 2);
+        assert_eq!(v, vec!["xx", "xxa", "xxab", "xxb"]);
+    }
+
+    /// `n` random strings of `min_len` to `max_len` bytes from `alphabet`,
+    /// the same for each `seed`.
+    pub fn random_strings(n: usize, min_len: usize, max_len: usize,
+                          alphabet: &str, seed: usize) -> Vec<String> {
+        let seed: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let chars: Vec<char> = alphabet.chars().collect();
+        (0..n).map(|_| {
+            let len = rng.gen_range(min_len, max_len + 1);
+            (0..len).map(|_| chars[rng.gen_range(0, chars.len())]).collect()
+        }).collect()
+    }
+
+    /// `n` random integers, the same for each `seed`.
+    pub fn random_u64s(n: usize, seed: usize) -> Vec<u64> {
+        let seed: &[_] = &[seed];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        rng.gen_iter().take(n).collect()
+    }
+
+    /// The words of the example for MSD string sort (page 706).
+    pub const SHE_SELLS: &str = "she sells seashells by the sea shore the \
+                                 shells she sells are surely seashells";
+}
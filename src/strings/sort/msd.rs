@@ -0,0 +1,140 @@
+//! Algorithm 5.2 MSD string sort.
+//!
+//! MSD string sort sorts strings of any length by key-indexed counting on
+//! their first byte, and then sorts the strings that start with each byte by
+//! their second byte, and so on. A string that ends comes before the strings
+//! that go on, so a prefix comes first. Small subarrays are sorted with
+//! insertion sort, since key-indexed counting on a few strings spends most of
+//! its time on the counts. The subarrays that are left to sort are kept on a
+//! stack instead of recursing, so long common prefixes can't overflow the call
+//! stack, though they still take a pass per byte.
+
+use super::{digit, insertion_sort, permute, R};
+
+/// MSD string sort is stable: key-indexed counting and insertion sort both
+/// are.
+pub const STABLE: bool = true;
+
+// Subarrays with at most this many strings are sorted with insertion sort.
+const CUTOFF: usize = 15;
+
+/// Sort `v` using MSD string sort.
+pub fn sort<S: AsRef<[u8]>>(v: &mut [S]) {
+    let mut dest = vec![0; v.len()];
+    // subarrays `v[lo..hi]` of strings that agree on their first `d` bytes
+    let mut stack = vec![(0, v.len(), 0)];
+    while let Some((lo, hi, d)) = stack.pop() {
+        if hi <= lo + CUTOFF {
+            insertion_sort(&mut v[lo..hi], d);
+            continue;
+        }
+        // count[r] is the number of strings whose digit is r - 1, where a
+        // string that ends at byte `d` has the digit 0
+        let mut count = [0; R + 2];
+        for s in &v[lo..hi] {
+            count[digit(s, d) + 1] += 1;
+        }
+        for r in 0..R+1 {
+            count[r + 1] += count[r];
+        }
+        for (i, s) in v[lo..hi].iter().enumerate() {
+            let r = digit(s, d);
+            dest[lo + i] = count[r];
+            count[r] += 1;
+        }
+        permute(&mut v[lo..hi], &mut dest[lo..hi]);
+        // count[r] is now the end of the strings with digit r, and those that
+        // ended are sorted already
+        for r in 1..R+1 {
+            if count[r] - count[r - 1] > 1 {
+                stack.push((lo + count[r - 1], lo + count[r], d + 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sorting::merge::top_down;
+    use super::sort;
+    use super::super::tests::{random_strings, SHE_SELLS};
+
+    #[test]
+    fn sort_works() {
+        let mut v: Vec<&str> = SHE_SELLS.split(' ').collect();
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort(&mut v);
+        assert_eq!(v, expected);
+        assert_eq!(&v[..3], &["are", "by", "sea"]);
+    }
+
+    #[test]
+    fn sort_works_for_random_strings() {
+        for &(n, alphabet) in &[(10, "ab"), (1000, "ab"), (1000, "ACGT"),
+                                (5000, "abcdefghijklmnopqrstuvwxyz")] {
+            let input = random_strings(n, 0, 12, alphabet, n);
+            let mut expected = input.clone();
+            top_down::sort(&mut expected);
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, expected);
+
+            let mut bytes: Vec<&[u8]> = input.iter().map(|s| s.as_bytes())
+                                             .collect();
+            sort(&mut bytes);
+            assert!(bytes.iter().zip(&expected)
+                         .all(|(a, b)| *a == b.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn sort_puts_prefixes_first() {
+        let mut v = vec!["abc", "", "ab", "abcd", "a", "b", "", "abc"];
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort(&mut v);
+        assert_eq!(v, expected);
+
+        // more than the cutoff, so that counting sees strings end
+        let mut v: Vec<String> = (0..100).map(|i| "x".repeat(i % 20))
+                                         .collect();
+        let mut expected = v.clone();
+        top_down::sort(&mut expected);
+        sort(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn sort_works_for_long_common_prefixes() {
+        let prefix = "p".repeat(5000);
+        let input: Vec<String> = random_strings(100, 0, 3, "ab", 1).iter()
+            .map(|s| format!("{}{}", prefix, s)).collect();
+        let mut expected = input.clone();
+        top_down::sort(&mut expected);
+        let mut v = input;
+        sort(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        // sort by the first byte only, through a type that only shows that
+        struct First(&'static str, usize);
+        impl AsRef<[u8]> for First {
+            fn as_ref(&self) -> &[u8] { &self.0.as_bytes()[..1] }
+        }
+        let words: Vec<&str> = SHE_SELLS.split(' ').collect();
+        let mut v: Vec<First> = words.iter().enumerate()
+                                     .map(|(i, w)| First(w, i)).collect();
+        sort(&mut v);
+        let mut expected: Vec<(u8, usize)> =
+            words.iter().enumerate().map(|(i, w)| (w.as_bytes()[0], i))
+                 .collect();
+        expected.sort();
+        let order: Vec<usize> = v.iter().map(|f| f.1).collect();
+        let expected: Vec<usize> = expected.iter().map(|e| e.1).collect();
+        assert_eq!(order, expected);
+    }
+
+}
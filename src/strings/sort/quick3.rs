@@ -0,0 +1,160 @@
+//! Algorithm 5.3 Three-way string quicksort.
+//!
+//! Three-way string quicksort partitions strings into those whose byte `d` is
+//! less than, equal to and greater than the byte `d` of a pivot, and then
+//! sorts the less and greater parts by byte `d` again and the equal part by
+//! byte `d + 1`. Unlike MSD string sort, it doesn't count over the whole
+//! alphabet for each subarray, so strings with long common prefixes take
+//! about one pass over the prefix, and unlike quicksort, it never compares the
+//! bytes of a prefix that's known to be equal again. Small subarrays are
+//! sorted with insertion sort, and the subarrays that are left to sort are
+//! kept on a stack instead of recursing. The input is shuffled first, so that
+//! the pivots are random.
+
+use rand::{self, Rng};
+
+use super::{digit, insertion_sort};
+
+/// Three-way string quicksort isn't stable: partitioning exchanges strings
+/// over long distances.
+pub const STABLE: bool = false;
+
+// Subarrays with at most this many strings are sorted with insertion sort.
+const CUTOFF: usize = 15;
+
+/// Sort `v` using three-way string quicksort.
+pub fn sort<S: AsRef<[u8]>>(v: &mut [S]) {
+    rand::thread_rng().shuffle(v);
+    // subarrays `v[lo..hi]` of strings that agree on their first `d` bytes
+    let mut stack = vec![(0, v.len(), 0)];
+    while let Some((lo, hi, d)) = stack.pop() {
+        if hi <= lo + CUTOFF {
+            insertion_sort(&mut v[lo..hi], d);
+            continue;
+        }
+        let (lt, gt) = partition(v, lo, hi, d);
+        stack.push((lo, lt, d));
+        stack.push((gt, hi, d));
+        // strings that ended at byte `d` are equal
+        if digit(&v[lt], d) > 0 {
+            stack.push((lt, gt, d + 1));
+        }
+    }
+}
+
+// Partition `v[lo..hi]` around the byte `d` of `v[lo]`, returning `(lt, gt)`
+// such that the strings in `v[lo..lt]` have a smaller byte `d`, those in
+// `v[lt..gt]` the same one and those in `v[gt..hi]` a greater one.
+fn partition<S: AsRef<[u8]>>(v: &mut [S], lo: usize, hi: usize, d: usize)
+    -> (usize, usize) {
+    let pivot = digit(&v[lo], d);
+    let (mut lt, mut i, mut gt) = (lo, lo + 1, hi);
+    while i < gt {
+        let t = digit(&v[i], d);
+        if t < pivot {
+            v.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if t > pivot {
+            gt -= 1;
+            v.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use sorting::merge::top_down;
+    use super::{partition, sort};
+    use super::super::tests::{random_strings, SHE_SELLS};
+
+    fn check<S: AsRef<[u8]> + Clone + Ord + Debug>(input: &[S]) {
+        let mut expected = input.to_vec();
+        top_down::sort(&mut expected);
+        let mut v = input.to_vec();
+        sort(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn partition_works() {
+        let mut v = vec!["xb", "xd", "xa", "x", "xb", "xc", "xb"];
+        let (lt, gt) = partition(&mut v, 0, 7, 1);
+        assert_eq!((lt, gt), (2, 5));
+        assert!(v[..lt].iter().all(|s| s < &"xb"));
+        assert!(v[lt..gt].iter().all(|s| s == &"xb"));
+        assert!(v[gt..].iter().all(|s| s > &"xb"));
+    }
+
+    #[test]
+    fn sort_works() {
+        let mut v: Vec<&str> = SHE_SELLS.split(' ').collect();
+        check(&v);
+        sort(&mut v);
+        assert_eq!(&v[..3], &["are", "by", "sea"]);
+    }
+
+    #[test]
+    fn sort_works_for_random_strings() {
+        for &(n, alphabet) in &[(10, "ab"), (1000, "ab"), (1000, "ACGT"),
+                                (5000, "abcdefghijklmnopqrstuvwxyz")] {
+            let input = random_strings(n, 0, 12, alphabet, n);
+            check(&input);
+            let bytes: Vec<&[u8]> = input.iter().map(|s| s.as_bytes())
+                                         .collect();
+            check(&bytes);
+        }
+    }
+
+    #[test]
+    fn sort_puts_prefixes_first() {
+        check(&["abc", "", "ab", "abcd", "a", "b", "", "abc"]);
+        // more than the cutoff, so that partitioning sees strings end
+        let v: Vec<String> = (0..100).map(|i| "x".repeat(i % 20)).collect();
+        check(&v);
+        check(&vec![""; 100]);
+        check::<&str>(&[]);
+    }
+
+    #[test]
+    fn sort_works_for_long_common_prefixes() {
+        let prefix = "p".repeat(5000);
+        let v: Vec<String> = random_strings(100, 0, 3, "ab", 1).iter()
+            .map(|s| format!("{}{}", prefix, s)).collect();
+        check(&v);
+    }
+
+    #[test]
+    fn sort_works_for_urls_and_paths() {
+        let hosts = ["https://example.com/", "https://example.com:8080/",
+                     "https://example.org/", "http://example.com/"];
+        let dirs = ["", "docs/", "docs/api/", "docs/api/v2/", "static/img/"];
+        let names = random_strings(2000, 1, 8, "abcdef", 1);
+        let urls: Vec<String> = names.iter().enumerate().map(|(i, name)| {
+            format!("{}{}{}.html", hosts[i % hosts.len()],
+                    dirs[i / hosts.len() % dirs.len()], name)
+        }).collect();
+        check(&urls);
+
+        let root = "/home/user/projects/algorithms/src/";
+        let paths: Vec<String> = names.iter().enumerate().map(|(i, name)| {
+            format!("{}{}{}", root, "sub/".repeat(i % 7), name)
+        }).collect();
+        check(&paths);
+        let bytes: Vec<&[u8]> = paths.iter().map(|s| s.as_bytes()).collect();
+        check(&bytes);
+    }
+
+    #[test]
+    fn sort_works_for_duplicates() {
+        let v: Vec<String> = random_strings(3000, 0, 3, "ab", 2);
+        check(&v);
+        let v: Vec<String> = (0..1000).map(|i| format!("key{}", i % 3))
+                                      .collect();
+        check(&v);
+    }
+}